@@ -0,0 +1,181 @@
+#[cfg(feature = "models")]
+use rust_bert::pipelines::common::{ModelResource, ModelType};
+#[cfg(feature = "models")]
+use rust_bert::pipelines::summarization::{SummarizationConfig, SummarizationModel};
+#[cfg(feature = "models")]
+use rust_bert::resources::LocalResource;
+use std::path::{Path, PathBuf};
+use crate::analyze::SummarySentence;
+use crate::models::ModelError;
+use crate::pdf::Document;
+use crate::segment;
+
+//BART/T5 read at most 1024/512 tokens; ~400 words stays under both
+const MAX_CHUNK_WORDS: usize = 400;
+//Chunks summarized per model call (keeps memory in check on CPU-only machines)
+const BATCH_SIZE: usize = 4;
+
+//Stand-in without the `models` feature: it has no values, so code holding one can never run
+#[cfg(not(feature = "models"))]
+pub enum SummarizationModel {}
+
+#[cfg(not(feature = "models"))]
+impl SummarizationModel {
+    fn summarize(&self, _texts: &[&str]) -> Result<Vec<String>, ModelError> {
+        match *self {}
+    }
+}
+
+#[cfg(not(feature = "models"))]
+pub fn load_model(_model_dir: Option<&Path>) -> Result<SummarizationModel, ModelError> {
+    Err(ModelError("abstractive summarization"))
+}
+
+//Load a summarization model from a local directory (offline) or the default remote BART-CNN weights.
+//Expected files: rust_model.ot, config.json and either vocab.json + merges.txt (BART) or spiece.model (T5)
+#[cfg(feature = "models")]
+pub fn load_model(model_dir: Option<&Path>) -> Result<SummarizationModel, ModelError> {
+    let Some(dir) = model_dir else {
+        return SummarizationModel::new(Default::default());
+    };
+
+    let local = |name: &str| LocalResource { local_path: dir.join(name) };
+    let config: SummarizationConfig = if dir.join("spiece.model").exists() {
+        SummarizationConfig::new(
+            ModelType::T5,
+            ModelResource::Torch(Box::new(local("rust_model.ot"))),
+            local("config.json"),
+            local("spiece.model"),
+            None,
+        )
+    } else {
+        SummarizationConfig::new(
+            ModelType::Bart,
+            ModelResource::Torch(Box::new(local("rust_model.ot"))),
+            local("config.json"),
+            local("vocab.json"),
+            Some(local("merges.txt")),
+        )
+    };
+    SummarizationModel::new(config)
+}
+
+//Loads the model on first use so a server that never gets an abstractive request skips the load
+pub struct LazySummarizer {
+    model_dir: Option<PathBuf>,
+    model: Option<SummarizationModel>,
+}
+
+impl LazySummarizer {
+    pub fn new(model_dir: Option<PathBuf>) -> Self {
+        LazySummarizer { model_dir, model: None }
+    }
+
+    //Model name and version shown in reports
+    pub fn version(&self) -> String {
+        match &self.model_dir {
+            Some(dir) => format!("rust-bert 0.23 ({})", dir.display()),
+            None => "rust-bert 0.23 (bart-large-cnn)".to_string(),
+        }
+    }
+
+    pub fn get(&mut self) -> Result<&SummarizationModel, ModelError> {
+        if self.model.is_none() {
+            self.model = Some(load_model(self.model_dir.as_deref())?);
+        }
+        Ok(self.model.as_ref().unwrap())
+    }
+}
+
+//A piece of the lecture small enough for the model, plus the page it starts on
+struct Chunk {
+    text: String,
+    page: u32,
+}
+
+//Pack whole sentences of consecutive pages into chunks of at most MAX_CHUNK_WORDS words
+//(a sentence longer than that is cut by words)
+fn chunk_document(document: &Document) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut start_page: u32 = 0;
+
+    for page in &document.pages {
+        for sentence in segment::split_sentences(&page.text) {
+            let sentence_words: Vec<&str> = sentence.split_whitespace().collect();
+            if !words.is_empty() && words.len() + sentence_words.len() > MAX_CHUNK_WORDS {
+                chunks.push(Chunk { text: words.join(" "), page: start_page });
+                words.clear();
+            }
+            for word in sentence_words {
+                if words.is_empty() {
+                    start_page = page.number;
+                }
+                words.push(word.to_string());
+                if words.len() == MAX_CHUNK_WORDS {
+                    chunks.push(Chunk { text: words.join(" "), page: start_page });
+                    words.clear();
+                }
+            }
+        }
+    }
+    if !words.is_empty() {
+        chunks.push(Chunk { text: words.join(" "), page: start_page });
+    }
+    chunks
+}
+
+//Summarize chunks in small batches, keeping each chunk's page
+fn summarize_chunks(model: &SummarizationModel, chunks: &[Chunk]) -> Result<Vec<Chunk>, ModelError> {
+    let mut summaries: Vec<Chunk> = Vec::new();
+    for batch in chunks.chunks(BATCH_SIZE) {
+        let texts: Vec<&str> = batch.iter().map(|c| c.text.as_str()).collect();
+        let outputs: Vec<String> = model.summarize(&texts)?;
+        for (chunk, summary) in batch.iter().zip(outputs) {
+            summaries.push(Chunk { text: summary.trim().to_string(), page: chunk.page });
+        }
+    }
+    Ok(summaries)
+}
+
+//Abstractive summary (map-reduce): summarize every chunk, then keep summarizing groups of
+//neighbouring summaries until at most num_paragraphs remain
+pub fn summarize_document(model: &SummarizationModel, document: &Document, num_paragraphs: usize) -> Result<Vec<SummarySentence>, ModelError> {
+    let num_paragraphs: usize = num_paragraphs.max(1);
+    let mut summaries: Vec<Chunk> = summarize_chunks(model, &chunk_document(document))?;
+
+    while summaries.len() > num_paragraphs {
+        //Group size so each pass cuts the count roughly to num_paragraphs; a group also closes
+        //early when the next summary would overflow the model input
+        let group_size: usize = summaries.len().div_ceil(num_paragraphs);
+        let mut grouped: Vec<Chunk> = Vec::new();
+        let mut members: usize = 0;
+        for summary in &summaries {
+            let fits: bool = match grouped.last() {
+                Some(group) => members < group_size
+                    && group.text.split_whitespace().count() + summary.text.split_whitespace().count() <= MAX_CHUNK_WORDS,
+                None => false,
+            };
+            if fits {
+                let group: &mut Chunk = grouped.last_mut().unwrap();
+                group.text.push(' ');
+                group.text.push_str(&summary.text);
+                members += 1;
+            } else {
+                grouped.push(Chunk { text: summary.text.clone(), page: summary.page });
+                members = 1;
+            }
+        }
+        //Summaries too long to combine: stop rather than loop forever
+        if grouped.len() == summaries.len() {
+            break;
+        }
+        summaries = summarize_chunks(model, &grouped)?;
+    }
+
+    Ok(summaries
+        .into_iter()
+        .filter(|s| !s.text.is_empty())
+        .map(|s| SummarySentence { text: s.text, page: s.page, start: None, end: None })
+        .collect())
+}
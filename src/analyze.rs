@@ -1,235 +1,471 @@
-use rust_bert::pipelines::ner::{NERModel, Entity};
 use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use clap::ValueEnum;
+use fancy_regex::Regex;
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use crate::abstractive::{self, LazySummarizer};
+use crate::config::{self, AnalysisConfig};
+use crate::lang::StopwordList;
+use crate::models::ModelError;
+use crate::ner::{self, AggregatedEntity, NERModel};
+use crate::pdf::{Document, Section};
+use crate::segment::{self, Sentence};
+
+//Entity found by NER plus where it was found (start/end are char offsets in the page text)
+#[derive(Debug, Clone, Serialize)]
+pub struct PageEntity {
+    pub word: String,
+    pub label: String,
+    pub score: f64,
+    pub page: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+//Summary sentence plus where it was taken from: the page and its char offsets in the page text
+//(None for abstractive summaries, which write their own sentences)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarySentence {
+    pub text: String,
+    pub page: u32,
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
+
+//Which summarizer to use: keyword hit counting, TextRank over sentence similarity,
+//or an abstractive seq2seq model (BART/T5) that writes its own sentences
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SummaryMethod {
+    #[default]
+    Keyword,
+    Textrank,
+    Abstractive,
+}
+
+const TEXTRANK_DAMPING: f32 = 0.85;
+const TEXTRANK_ITERATIONS: usize = 20;
+
+//Keyword, the score its extractor gave it and every page that mentions it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordLocation {
+    pub keyword: String,
+    pub score: f64,
+    pub pages: Vec<u32>,
+}
 
 #[allow(dead_code)]
 
 //extract named entity details: word, label, score etc.
-pub fn extract_entities_ner(model:&mut NERModel,text:&str) -> Vec<Entity> {
-    let entities: Vec<Vec<rust_bert::pipelines::ner::Entity>> = model.predict(&[text]);
-    //Return as Vec<Entity>
-    entities.get(0).cloned().unwrap_or_else(Vec::new)
+//Long lectures are chunked (with overlap) so nothing past the model's token limit is lost
+pub fn extract_entities_ner(model:&mut NERModel, document:&Document) -> Vec<PageEntity> {
+    ner::predict_chunked(model, document)
 }
 
-pub fn extract_keywords_ner(model:&mut NERModel, text:&str) -> Vec<String> {
-    let entities: Vec<Vec<rust_bert::pipelines::ner::Entity>> = model.predict(&[text]);
-    let mut keywords: Vec<String> = Vec::new();
+//Token-level entities grouped into whole entities with confidence and mention counts
+pub fn extract_entities_aggregated(model:&mut NERModel, document:&Document) -> Vec<AggregatedEntity> {
+    let entities: Vec<PageEntity> = extract_entities_ner(model, document);
+    ner::aggregate_entities(document, &entities)
+}
+
+//Whole entity texts (no "##" fragments or split names) scored by mention count, most mentioned first
+pub fn extract_keywords_ner(model:&mut NERModel, document:&Document) -> Vec<(String, f64)> {
+    let mut keywords: Vec<(String, f64)> = Vec::new();
     let mut seen: HashSet<String> = HashSet::new();
-    for entity in entities.get(0).unwrap_or(&Vec::new()) {
-        if seen.insert(entity.word.clone()) {
-            keywords.push(entity.word.clone());
+    for entity in extract_entities_aggregated(model, document) {
+        if seen.insert(entity.text.to_lowercase()) {
+            keywords.push((entity.text, entity.count as f64));
         }
     }
     keywords
 }
-    #[allow(dead_code)]
-    //1.Load Stopwords (basic keyword extraction)
-    pub fn extract_keywords(text:&str) -> Vec<String> {
-    let stopwords: HashSet<&str> = [
-        "a","about","above","after","again","against","all","am","an","and","any","are",
-        "aren't","as","at","be","because","been","before","being","below","between","both",
-        "but","by","can't","cannot","could","couldn't","did","didn't","do","does","doesn't",
-        "doing","don't","down","during","each","few","for","from","further","had","hadn't","has",
-        "hasn't","have","haven't","having","he","he'd","he'll","he's","her","here","here's","hers",
-        "herself","him","himself","his","how","how's","i","i'd","i'll","i'm","i've","if","in","into","is",
-        "isn't","it","it's","its","itself","just","ll","may","me","mightn't","more","most","mustn't","my",
-        "myself","needn't","no","nor","not","now","of","off","on","once","only","or","other","our","ours",
-        "ourselves","out","over","own","re","s","same","shan't","she","she'd","she'll","she's","should",
-        "shouldn't","so","some","such","t","than","that","that's","the","their","theirs","them","themselves","then",
-        "there","there's","these","they","they'd","they'll","they're","they've","this","those","through","to","too","under","until",
-        "up","ve","very","was","wasn't","we","we'd","we'll","we're","we've","were","weren't","what","what's","when","when's",
-        "where","where's","which","while","who","who's","whom","why","why's","will","with","won't","would","wouldn't","y","you","you'd","you'll",
-        "you're","you've","your","yours","yourself","yourselves"
-    ].iter().cloned().collect();
 
-    let mut freq: HashMap<String, usize> = HashMap::new();
+//Attach the pages each keyword appears on
+pub fn locate_keywords(document:&Document, keywords:&[(String, f64)]) -> Vec<KeywordLocation> {
+    keywords
+        .iter()
+        .map(|(kw, score)| KeywordLocation {
+            keyword: kw.clone(),
+            score: *score,
+            pages: document.pages_containing(kw),
+        })
+        .collect()
+}
 
-    //splite text into words and count frequency
-    for word in text
+//Lowercase candidate keywords: alphabetic, 3-19 bytes long, not a stopword
+//(shared by frequency ranking and the TF-IDF corpus index)
+pub fn tokenize_words(text:&str, stopwords:&StopwordList) -> Vec<String> {
+    text
         .split(|c:char|!c.is_alphabetic())
         .map(|w: &str| w.to_lowercase())
-        .filter(|w: &String|w.len() > 2 && w.len() < 20 && !stopwords.contains(w.as_str()))
-    {
+        .filter(|w: &String|w.len() > 2 && w.len() < 20 && !stopwords.contains(w))
+        .collect()
+}
+
+    //1.Load Stopwords (basic keyword extraction), scored by how often they occur
+    pub fn extract_keywords(text:&str, top_n: usize, stopwords:&StopwordList) -> Vec<(String, f64)> {
+    let mut freq: HashMap<String, usize> = HashMap::new();
+
+    //splite text into words and count frequency
+    for word in tokenize_words(text, stopwords) {
         *freq.entry(word).or_insert(0) += 1;
     }
 
-    //Get top 10 most frequency keywords
+    //Get top N most frequency keywords
     let mut keywords:Vec<(String,usize)> = freq.into_iter().collect();
     keywords.sort_by(|a, b| b.1.cmp(&a.1)); 
-    keywords.into_iter().take(10).map(|(w, _)| w).collect()
+    keywords.into_iter().take(top_n).map(|(w, count)| (w, count as f64)).collect()
 }
 
-pub fn extract_summary(text:&str, num_sentences: usize, keywords:&[String]) -> Vec<String> {
-    //Simple sentence splitting by '.'
-    let sentences: Vec<&str> = text.split('.').collect();
+//skip sentences with legal/copyright/resource words or URLs, or too short (both set in the config file)
+fn is_relevant_sentence(sentence:&str) -> bool {
+    let settings: &AnalysisConfig = &config::get().analysis;
+    let s: String = sentence.trim().to_lowercase();
+    !(settings.summary_filter_words.iter().any(|word| s.contains(&word.to_lowercase())) ||
+      s.len() < settings.min_sentence_length)
+}
+
+pub fn extract_summary(document:&Document, num_sentences: usize, keywords:&[String]) -> Vec<SummarySentence> {
+    //Sentence splitting done per page so every sentence keeps its page number and offsets
+    let sentences: Vec<(Sentence, u32)> = document.pages
+        .iter()
+        .flat_map(|page| segment::segment(&page.text).into_iter().map(move |s| (s, page.number)))
+        .collect();
 
     //Filter out irrelevant sentences
-    let filtered:Vec<&str> = sentences
+    let filtered:Vec<(&Sentence, u32)> = sentences
         .iter()
-        .filter(|s| {
-            let s: String = s.trim().to_lowercase();
-            //skip sentences with legalcopyright/resource words or URLS, or too short
-            !(s.contains("copyright") ||
-              s.contains("right reserved") ||
-              s.contains("legal") ||
-              s.contains("resources") ||
-              s.contains("published") ||
-              s.contains("www") ||
-              s.contains("http") ||
-              s.len() < 20)
-            })
-        .cloned()
+        .filter(|(s, _)| is_relevant_sentence(&s.text))
+        .map(|(s, page)| (s, *page))
         .collect();
 
     //ranked sentences based on keyword presence
-    let mut scored:Vec<(&str, usize)> = filtered
+    let mut scored:Vec<(&Sentence, u32, usize)> = filtered
         .iter()
-        .map(|&sentence| {
-            let score = keywords.iter().filter(|kw| sentence.contains(&kw.to_lowercase())).count();
-            (sentence, score)
+        .map(|&(sentence, page)| {
+            let text: String = sentence.text.to_lowercase();
+            let score = keywords.iter().filter(|kw| text.contains(&kw.to_lowercase())).count();
+            (sentence, page, score)
         })
         .collect();
 
     //sort by score descending, then take top N
-    scored.sort_by(|a,b|b.1.cmp(&a.1));
-    scored.iter().take(num_sentences).map(|(s,_)| s.trim().to_string()).collect()
+    scored.sort_by_key(|s| std::cmp::Reverse(s.2));
+    scored
+        .iter()
+        .take(num_sentences)
+        .map(|(s, page, _)| SummarySentence { text: s.text.trim().to_string(), page: *page, start: Some(s.start), end: Some(s.end) })
+        .collect()
     
 }
 
-    /*2. Split text into candidates_phrases from stopwords and punctuations 
-    let re = Regex::new(r#"[.,;!?()\[\]"\n]"#).unwrap();
-    let cleaned_text = re.replace_all(text, ""); 
+//Options for the RAKE keyphrase extractor
+#[derive(Debug, Clone)]
+pub struct RakeOptions {
+    pub top_n: usize,
+    //shortest phrase kept, in characters
+    pub min_phrase_length: usize,
+    //longer candidate phrases are dropped, as in the original RAKE paper
+    pub max_words: usize,
+}
 
-    let words: Vec<String> = cleaned_text
-        .split_whitespace()
-        .map(|w| w.trim().to_lowercase())
-        .filter(|w| !w.is_empty() && !stopwords.contains(w))
-        .collect();
+impl Default for RakeOptions {
+    fn default() -> Self {
+        RakeOptions { top_n: 10, min_phrase_length: 3, max_words: 3 }
+    }
+}
 
-    let mut candidates_phrases: Vec<String> = Vec::new();
-    let mut phrase: Vec<String> = Vec::new();
+//RAKE (Rapid Automatic Keyword Extraction): multi-word keyphrases like "gradient descent"
+pub fn extract_keyphrases_rake(text:&str, options:&RakeOptions, stopwords:&StopwordList) -> Vec<(String, f64)> {
+    //1. Split text into fragments at punctuation and line breaks (slide bullets have no periods)
+    let delimiters = Regex::new(r#"[.,;:!?()\[\]{}"“”•–—\n]"#).unwrap();
 
-    for word in words {
-        if stopwords.contains(&word) {
-            if !phrase.is_empty() {
-                candidates_phrases.push(phrase.join(" "));
-                phrase.clear();
+    //2. Split fragments into candidate phrases at stopwords and non-word tokens
+    let mut candidates_phrases: Vec<Vec<String>> = Vec::new();
+    for fragment in delimiters.split(text).filter_map(|f| f.ok()) {
+        let mut phrase: Vec<String> = Vec::new();
+        for token in fragment.split_whitespace() {
+            let word: String = token
+                .trim_matches(|c:char| !c.is_alphanumeric())
+                .to_lowercase();
+            let is_word: bool = word.chars().any(|c| c.is_alphabetic()) && word.chars().count() > 1;
+            if !is_word || stopwords.contains(&word) {
+                if !phrase.is_empty() {
+                    candidates_phrases.push(std::mem::take(&mut phrase));
+                }
+            } else {
+                phrase.push(word);
             }
-        } else {
-            phrase.push(word);
         }
-    }
-    //Push last phrase if not empty
-    if !phrase.is_empty() {
-        candidates_phrases.push(phrase.join(" "));
-    }
-
-    //3.Calculate word frequency and degree
-    let mut word_freq: HashMap<String,usize>=HashMap::new();
-    let mut word_degree: HashMap<String,usize>=HashMap::new();
-    for phrase in &candidates_phrases {
-        let words: Vec<&str> = phrase.split_whitespace().collect();
-        let degree = if words.len() > 1 { words.len() - 1 } else { 0 };
-        for word in &words {
-            *word_freq.entry(word.to_string()).or_insert(0) += 1;
-            *word_degree.entry(word.to_string()).or_insert(0) += degree;
+        //Push last phrase if not empty
+        if !phrase.is_empty() {
+            candidates_phrases.push(phrase);
         }
     }
+    candidates_phrases.retain(|words| {
+        words.len() <= options.max_words && words.join(" ").chars().count() >= options.min_phrase_length
+    });
 
-    let mut word_score: HashMap<String,f64>=HashMap::new();
-    for (word, &freq) in &word_freq {
-        let degree: &usize = word_degree.get(word).unwrap_or(&0); 
-        word_score.insert(word.clone(), (*degree as f64 + freq as f64) / freq as f64);
+    //3.Calculate word frequency and degree (co-occurrences inside phrases, including itself)
+    let mut word_freq: HashMap<&str,usize>=HashMap::new();
+    let mut word_degree: HashMap<&str,usize>=HashMap::new();
+    for words in &candidates_phrases {
+        for word in words {
+            *word_freq.entry(word.as_str()).or_insert(0) += 1;
+            *word_degree.entry(word.as_str()).or_insert(0) += words.len();
+        }
     }
 
-    //Score candidates_phrases
-    let mut phrase_score: HashMap<String, f32> = HashMap::new();
-    for phrase in &candidates_phrases {
-        let words: Vec<&str> = phrase.split_whitespace().collect();
+    //4.Score candidates_phrases: sum of degree/frequency of their words
+    let mut phrase_score: HashMap<String, f64> = HashMap::new();
+    for words in &candidates_phrases {
         let score: f64 = words
             .iter()
-            .map(|w| word_score.get(&w.to_string()).unwrap_or(&0.0))
+            .map(|w| word_degree[w.as_str()] as f64 / word_freq[w.as_str()] as f64)
             .sum();
-        phrase_score.insert(phrase.clone(), score as f32);
+        phrase_score.insert(words.join(" "), score);
     }
 
-    //Sort and return top N phrases/keywords
-    let mut phrases: Vec<(&String, &f32)> = phrase_score.iter().collect();
-    phrases.sort_by(|a, b| b.1.partial_cmp(a.1).unwrap()); //a=&String, b=&f32
-    phrases.into_iter().take(10).map(|(p, _)| p.clone()).collect()
+    //Sort and return top N phrases/keywords (ties alphabetically so output is stable)
+    let mut phrases: Vec<(String, f64)> = phrase_score.into_iter().collect();
+    phrases.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+    phrases.into_iter().take(options.top_n).collect()
 }
 
-    //Split text convert into sentences (basic version)
-    fn split_text_into_sentences(text:&str) -> Vec<String> {
-        let re = Regex::new(r"(?m)(?<!\w\.\w.)(?<![A-Z][a-z]\.)(?<=\.|\?)\s").unwrap();
-        re.split(text)
-            .map(|res|res.expect("Valid regex split").trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect()
-    }
 
-    //Helper function for sentence similarity by word overlap
-    fn sentence_similarity(s1:&str, s2:&str) -> f32 {
-        let set1: HashSet<&str> = s1.split_whitespace().collect();
-        let set2: HashSet<&str> = s2.split_whitespace().collect();
-        let common:usize = set1.intersection(&set2).count() ;
-        if common == 0 {
-            0.0
-        } else {
-            (common as f32) / ((set1.len() + set2.len()) as f32 / 2.0)
-        }
+//Helper function for sentence similarity by word overlap
+fn sentence_similarity(s1:&str, s2:&str) -> f32 {
+    let set1: HashSet<String> = s1.split_whitespace().map(|w| w.to_lowercase()).collect();
+    let set2: HashSet<String> = s2.split_whitespace().map(|w| w.to_lowercase()).collect();
+    let common:usize = set1.intersection(&set2).count();
+    if common == 0 {
+        0.0
+    } else {
+        (common as f32) / ((set1.len() + set2.len()) as f32 / 2.0)
     }
-    */
-
-    //Textrank for sentence extraction
-    /*Similariy matrix
-    let mut similarity:Vec<Vec<f32>>=vec![vec![0.0; sentences_storage.len()]; sentences_storage.len()];
-    for i in 0..sentences_storage.len() {
-        for j in 0..sentences_storage.len() {
-            if i != j {
-                similarity[i][j] = sentence_similarity(&sentences_storage[i], &sentences_storage[j]);
-    */
-
+}
 
+//Textrank for sentence extraction: sentences are nodes, word overlap is the edge weight
+pub fn extract_summary_textrank(document:&Document, num_sentences: usize) -> Vec<SummarySentence> {
+    let sentences: Vec<(Sentence, u32)> = document.pages
+        .iter()
+        .flat_map(|page| {
+            segment::segment(&page.text)
+                .into_iter()
+                .map(move |s| (s, page.number))
+        })
+        .filter(|(s, _)| is_relevant_sentence(&s.text))
+        .collect();
 
-    
-            
+    //Similarity graph (only overlapping sentences get an edge)
+    let mut graph: UnGraph<usize, f32> = UnGraph::new_undirected();
+    let nodes: Vec<NodeIndex> = (0..sentences.len()).map(|i| graph.add_node(i)).collect();
+    for i in 0..sentences.len() {
+        for j in (i + 1)..sentences.len() {
+            let similarity: f32 = sentence_similarity(&sentences[i].0.text, &sentences[j].0.text);
+            if similarity > 0.0 {
+                graph.add_edge(nodes[i], nodes[j], similarity);
+            }
+        }
+    }
 
-    /*Initialize sentence scores
-    let mut scores:Vec<f32> = vec![1.0; sentences_storage.len()];
-    let mut new_scores:Vec<f32> = vec![1.0;sentences_storage.len()];
+    //Total edge weight of each sentence, used to normalize what it passes on
+    let weight_sums: Vec<f32> = nodes
+        .iter()
+        .map(|&n| graph.edges(n).map(|e| *e.weight()).sum())
+        .collect();
 
     //Iterative scoring (UPDATE SCORES)
-    for _ in 0..20 {
-        for i in 0..sentences_storage.len(){
-            let mut sum: f32 = 0.0;
-            //Sum of weighted scores from other sentences
-            for j in 0..sentences_storage.len() {
-                if i != j {
-                    let sim_sum:f32 = similarity[j].iter().sum::<f32>();
-                    if sim_sum != 0.0 {
-                    //weighted by similarity(i,j)
-                    sum += similarity[j][i] * scores[j] / sim_sum;
-                    } 
-                }
-            }
-            new_scores[i] = 0.15 + 0.85 * sum; //Damping factor
+    let mut scores: Vec<f32> = vec![1.0; sentences.len()];
+    for _ in 0..TEXTRANK_ITERATIONS {
+        let mut new_scores: Vec<f32> = vec![0.0; sentences.len()];
+        for &node in &nodes {
+            let i: usize = node.index();
+            //Sum of weighted scores from neighbouring sentences
+            let sum: f32 = graph
+                .edges(node)
+                .map(|e| {
+                    let j: usize = if e.source() == node { e.target().index() } else { e.source().index() };
+                    e.weight() * scores[j] / weight_sums[j]
+                })
+                .sum();
+            new_scores[i] = (1.0 - TEXTRANK_DAMPING) + TEXTRANK_DAMPING * sum;
         }
-        scores = new_scores.clone();
+        scores = new_scores;
     }
 
     //Select top N sentences
-    let mut indexed:Vec<(usize, f32)> = scores
-                                            .iter()
-                                            .enumerate()
-                                            .map(|(i, &score)| (i, score))
-                                            .collect();
-    indexed.sort_by(|a: &(usize, f32), b: &(usize, f32)| b.1.partial_cmp(&a.1).unwrap());
+    let mut indexed: Vec<(usize, f32)> = scores.into_iter().enumerate().collect();
+    indexed.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
     indexed
         .iter()
         .take(num_sentences)
-        .map(|(i, _)| sentences_storage[*i].clone())
+        .map(|&(i, _)| {
+            let (sentence, page) = &sentences[i];
+            SummarySentence { text: sentence.text.clone(), page: *page, start: Some(sentence.start), end: Some(sentence.end) }
+        })
         .collect()
-    
 }
-*/
 
+//Run whichever summarizer was picked on the CLI or in the web form
+//(the abstractive model is only loaded when it is actually picked)
+pub fn summarize(
+    document:&Document,
+    num_sentences: usize,
+    keywords:&[String],
+    method: SummaryMethod,
+    summarizer:&mut LazySummarizer,
+) -> Result<Vec<SummarySentence>, ModelError> {
+    match method {
+        SummaryMethod::Keyword => Ok(extract_summary(document, num_sentences, keywords)),
+        SummaryMethod::Textrank => Ok(extract_summary_textrank(document, num_sentences)),
+        SummaryMethod::Abstractive => abstractive::summarize_document(summarizer.get()?, document, num_sentences),
+    }
+}
+
+//Summary of one top-level outline section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionSummary {
+    pub title: String,
+    pub start_page: u32,
+    pub end_page: u32,
+    pub summary: Vec<SummarySentence>,
+}
+
+//Summarize every outline section on its own pages (same summarizer as the whole lecture);
+//empty when the PDF has no outline
+pub fn summarize_sections(
+    document:&Document,
+    num_sentences: usize,
+    keywords:&[String],
+    method: SummaryMethod,
+    summarizer:&mut LazySummarizer,
+) -> Result<Vec<SectionSummary>, ModelError> {
+    let mut sections: Vec<SectionSummary> = Vec::new();
+    for Section { title, start_page, end_page } in document.sections() {
+        let pages: Document = document.page_range(start_page, end_page);
+        let summary: Vec<SummarySentence> = summarize(&pages, num_sentences, keywords, method, summarizer)?;
+        sections.push(SectionSummary { title, start_page, end_page, summary });
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::Page;
+
+    fn document(pages: &[&str]) -> Document {
+        Document {
+            pages: pages.iter().enumerate().map(|(i, text)| Page { number: i as u32 + 1, text: text.to_string() }).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keyword_summary_matches_keywords_in_any_case() {
+        let document: Document = document(&["Every relation is stored as rows in tables.\nNormalization splits Tables to remove redundant data."]);
+        let summary: Vec<SummarySentence> = extract_summary(&document, 1, &["normalization".to_string(), "Tables".to_string()]);
+        assert_eq!(summary[0].text, "Normalization splits Tables to remove redundant data.");
+    }
+
+    #[test]
+    fn textrank_picks_the_central_sentence() {
+        let document: Document = document(&[
+            "Indexes speed up database queries considerably.\n\
+             Database queries use indexes and transactions on tables often.\n\
+             Transactions on tables keep every change consistent.",
+        ]);
+        let summary: Vec<SummarySentence> = extract_summary_textrank(&document, 1);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].text, "Database queries use indexes and transactions on tables often.");
+    }
+
+    #[test]
+    fn textrank_sentences_keep_their_page_and_offsets() {
+        let pages: [&str; 3] = [
+            "A relational database stores data in tables.",
+            "Each table in a relational database has a primary key.",
+            "A primary key identifies each row of the table.",
+        ];
+        let document: Document = document(&pages);
+        let summary: Vec<SummarySentence> = extract_summary_textrank(&document, 3);
+        assert_eq!(summary.len(), 3);
+        for sentence in &summary {
+            let page: &Page = &document.pages[sentence.page as usize - 1];
+            let chars: Vec<char> = page.text.chars().collect();
+            let (start, end) = (sentence.start.unwrap(), sentence.end.unwrap());
+            assert_eq!(chars[start..end].iter().collect::<String>(), sentence.text);
+        }
+    }
+
+    #[test]
+    fn textrank_skips_irrelevant_sentences() {
+        let document: Document = document(&[
+            "Database tables store rows of data.\n\
+             Copyright 2024 database tables rows data, all rights reserved.\n\
+             Tables rows data.\n\
+             Rows of data live in database tables too.",
+        ]);
+        let summary: Vec<SummarySentence> = extract_summary_textrank(&document, 5);
+        let texts: Vec<&str> = summary.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts.len(), 2);
+        assert!(!texts.iter().any(|t| t.contains("Copyright")));
+        assert!(!texts.contains(&"Tables rows data."));
+    }
+
+    #[test]
+    fn textrank_handles_empty_and_single_sentence_input() {
+        assert!(extract_summary_textrank(&document(&[]), 3).is_empty());
+        assert!(extract_summary_textrank(&document(&[""]), 3).is_empty());
+        let summary: Vec<SummarySentence> = extract_summary_textrank(&document(&["", "Normalization removes redundant data from tables."]), 3);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].text, "Normalization removes redundant data from tables.");
+        assert_eq!(summary[0].page, 2);
+    }
+
+    #[test]
+    fn similarity_is_word_overlap() {
+        assert_eq!(sentence_similarity("a b c d", "a b x y"), 0.5);
+        assert_eq!(sentence_similarity("Data tables", "data Tables"), 1.0);
+        assert_eq!(sentence_similarity("data", "keys"), 0.0);
+    }
+
+    fn rake(text: &str, options: RakeOptions) -> Vec<(String, f64)> {
+        extract_keyphrases_rake(text, &options, &StopwordList::new(crate::lang::Lang::English, &[]))
+    }
+
+    fn phrases(keyphrases: &[(String, f64)]) -> Vec<&str> {
+        keyphrases.iter().map(|(phrase, _)| phrase.as_str()).collect()
+    }
+
+    #[test]
+    fn rake_splits_candidates_at_stopwords_and_punctuation() {
+        let keyphrases: Vec<(String, f64)> = rake("Linear regression and the loss function; gradient descent (SGD).", RakeOptions::default());
+        assert_eq!(phrases(&keyphrases), vec!["gradient descent", "linear regression", "loss function", "sgd"]);
+    }
+
+    #[test]
+    fn rake_scores_phrases_by_degree_over_frequency() {
+        //neural and network: frequency 2, degree 3 + 2; training: frequency 1, degree 3
+        let keyphrases: Vec<(String, f64)> = rake("Neural network training\nneural network\ndeep learning", RakeOptions::default());
+        assert_eq!(
+            keyphrases,
+            vec![
+                ("neural network training".to_string(), 8.0),
+                ("neural network".to_string(), 5.0),
+                ("deep learning".to_string(), 4.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn rake_drops_short_and_long_phrases() {
+        let text: &str = "AI\nquery plan\nrelational database management system";
+        assert_eq!(phrases(&rake(text, RakeOptions::default())), vec!["query plan"]);
+        let options: RakeOptions = RakeOptions { min_phrase_length: 2, max_words: 4, ..RakeOptions::default() };
+        assert_eq!(phrases(&rake(text, options)), vec!["relational database management system", "query plan", "ai"]);
+        let options: RakeOptions = RakeOptions { top_n: 1, max_words: 4, ..RakeOptions::default() };
+        assert_eq!(phrases(&rake(text, options)), vec!["relational database management system"]);
+    }
+}
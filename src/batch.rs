@@ -0,0 +1,154 @@
+//Batch analysis of many lectures with one set of analyzers (the NER model is loaded once): one report
+//per lecture plus index.json in the output directory. Extraction runs in parallel (--jobs at a time),
+//the model stages take turns through the analyzers' locks; a failed lecture is recorded and skipped
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use crate::input;
+use crate::output::{self, OutputFormat, View};
+use crate::report::{self, AnalysisOptions, AnalysisReport, Analyzers};
+
+//One lecture in the batch index
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchEntry {
+    pub file: String,
+    //report file relative to the output directory, none when the analysis failed
+    pub report: Option<String>,
+    pub error: Option<String>,
+    pub pages: usize,
+    pub keywords: Vec<String>,
+    pub total_ms: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct BatchIndex {
+    pub analyzed: usize,
+    pub failed: usize,
+    pub lectures: Vec<BatchEntry>,
+}
+
+fn has_glob(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+//Lecture files named by the inputs: directories give their lecture files, glob patterns ("slides/week*.pdf")
+//the supported files they match, anything else is taken as a file. Each file is listed once, in input order
+pub fn collect_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        let path: &Path = Path::new(input);
+        if path.is_dir() {
+            files.extend(input::lectures_in(path).map_err(|e| format!("cannot read {}: {}", input, e))?);
+        } else if has_glob(input) {
+            let mut matches: Vec<PathBuf> = glob::glob(input)
+                .map_err(|e| format!("invalid pattern {}: {}", input, e))?
+                .filter_map(|entry| entry.ok())
+                .filter(|p| p.is_file() && input::is_supported(p))
+                .collect();
+            matches.sort();
+            files.extend(matches);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    files.retain(|p| seen.insert(p.clone()));
+    Ok(files)
+}
+
+fn extension(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Text => "txt",
+        OutputFormat::Json => "json",
+        OutputFormat::Yaml => "yaml",
+        OutputFormat::Markdown => "md",
+    }
+}
+
+//Report file name per lecture ("week1.pdf.json"); lectures of the same name in different directories get "-2", "-3"...
+fn report_names(files: &[PathBuf], format: OutputFormat) -> Vec<String> {
+    let mut used: HashMap<String, usize> = HashMap::new();
+    files
+        .iter()
+        .map(|path| {
+            let name: String = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "lecture".to_string());
+            let count: &mut usize = used.entry(name.clone()).or_insert(0);
+            *count += 1;
+            match *count {
+                1 => format!("{}.{}", name, extension(format)),
+                n => format!("{}-{}.{}", name, n, extension(format)),
+            }
+        })
+        .collect()
+}
+
+fn entry(file: String, report_name: &str, result: &Result<AnalysisReport, String>) -> BatchEntry {
+    match result {
+        Ok(report) => BatchEntry {
+            file,
+            report: Some(report_name.to_string()),
+            error: None,
+            pages: report.source.pages.len(),
+            keywords: report.keywords.iter().map(|k| k.keyword.clone()).collect(),
+            total_ms: report.timings.total_ms,
+        },
+        Err(e) => BatchEntry { file, report: None, error: Some(e.clone()), pages: 0, keywords: Vec::new(), total_ms: 0 },
+    }
+}
+
+//Analyze every file with at most `jobs` lectures in flight, write each report in `format` and index.json into `out_dir`
+pub async fn run_batch(
+    files: Vec<PathBuf>,
+    out_dir: &Path,
+    format: OutputFormat,
+    options: Arc<AnalysisOptions>,
+    analyzers: &Analyzers,
+    jobs: usize,
+) -> Result<BatchIndex, Box<dyn Error + Send + Sync>> {
+    std::fs::create_dir_all(out_dir)?;
+    let names: Vec<String> = report_names(&files, format);
+    let semaphore: Arc<Semaphore> = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks: JoinSet<Result<AnalysisReport, String>> = JoinSet::new();
+    //task id -> position in the input list, so a panicking lecture can still be reported
+    let mut positions: HashMap<tokio::task::Id, usize> = HashMap::new();
+    for (i, path) in files.iter().enumerate() {
+        let (file, options, analyzers, semaphore) = (path.to_string_lossy().to_string(), options.clone(), analyzers.clone(), semaphore.clone());
+        let handle = tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.map_err(|e| e.to_string())?;
+            report::analyze_lecture(&file, &file, &options, &analyzers).await.map_err(|e| e.to_string())
+        });
+        positions.insert(handle.id(), i);
+    }
+
+    let mut entries: Vec<Option<BatchEntry>> = vec![None; files.len()];
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, result) = match joined {
+            Ok((id, result)) => (id, result),
+            Err(e) => (e.id(), Err(format!("analysis panicked: {}", e))),
+        };
+        let i: usize = positions[&id];
+        let file: String = files[i].to_string_lossy().to_string();
+        //a report that cannot be written counts as a failed lecture too
+        let result: Result<AnalysisReport, String> = result.and_then(|report| {
+            output::render(&report, View::Full, format, true)
+                .and_then(|rendered| output::write(&rendered, Some(&out_dir.join(&names[i]))))
+                .map(|_| report)
+                .map_err(|e| format!("cannot write report: {}", e))
+        });
+        match &result {
+            Ok(_) => eprintln!("Analyzed {}", file),
+            Err(e) => eprintln!("Failed {}: {}", file, e),
+        }
+        entries[i] = Some(entry(file, &names[i], &result));
+    }
+
+    let lectures: Vec<BatchEntry> = entries.into_iter().flatten().collect();
+    let failed: usize = lectures.iter().filter(|e| e.error.is_some()).count();
+    let index: BatchIndex = BatchIndex { analyzed: lectures.len() - failed, failed, lectures };
+    std::fs::write(out_dir.join("index.json"), serde_json::to_string_pretty(&index)?)?;
+    Ok(index)
+}
@@ -0,0 +1,393 @@
+//Block classification between cleaning and normalization: every page is split into runs of prose,
+//bullet lists, code, tables and formulas. Keyword extraction and the summarizers turn code and
+//equations into tokens like "println" or "xij", so those blocks are taken out of the page text and
+//kept on the document (text as extracted, table cells split) for the reports.
+use regex::{Regex, RegexSet};
+use crate::pdf::{Block, BlockKind, Document};
+use crate::segment::BULLETS;
+
+//Code signals a run of lines needs in total ("return x;" on its own is not a listing)
+const MIN_CODE_SCORE: usize = 2;
+//Rows a table needs when its cells are separated by '|', tabs or aligned spaces...
+const MIN_TABLE_ROWS: usize = 2;
+//...and when only its numbers give it away (pdf-extract loses the column spacing)
+const MIN_NUMERIC_ROWS: usize = 3;
+//Up to this many non-code lines between two code lines belong to the listing (loop bodies, "end")
+const MAX_CODE_GAP: usize = 2;
+//A formula has at most this many words of four or more letters
+const MAX_FORMULA_WORDS: usize = 1;
+//Symbols that only show up in formulas
+const MATH_SYMBOLS: &[char] = &[
+    '∑', '∏', '∫', '∮', '√', '∂', '∞', '≤', '≥', '≠', '≈', '≡', '∈', '∉', '⊂', '⊆', '∪', '∩', '∀', '∃',
+    '±', '×', '÷', '⋅', '∇', 'Δ', 'α', 'β', 'γ', 'δ', 'ε', 'θ', 'λ', 'μ', 'π', 'σ', 'φ', 'ω', '²', '³',
+];
+const RELATIONS: &[char] = &['=', '<', '>', '≤', '≥', '≈', '≠', '≡', '∈', '⊂', '⊆'];
+
+//How a line looks as part of a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableLine {
+    No,
+    //cells split by '|', tabs or aligned spaces, or a "|---|---|" rule
+    Cells,
+    //mostly numbers ("2021 45 67.5%")
+    Numeric,
+}
+
+//One line of a page with what it looks like; `fallback` is its kind when the run it is in turns
+//out not to be code or a table after all
+#[derive(Debug, Clone)]
+struct Line<'a> {
+    text: &'a str,
+    kind: Option<BlockKind>,
+    fallback: BlockKind,
+    code_score: usize,
+    table: TableLine,
+    fenced: bool,
+}
+
+impl Line<'_> {
+    fn blank() -> Line<'static> {
+        Line { text: "", kind: None, fallback: BlockKind::Prose, code_score: 0, table: TableLine::No, fenced: false }
+    }
+}
+
+struct Patterns {
+    code: RegexSet,
+    numbered: Regex,
+    table_rule: Regex,
+    aligned: Regex,
+    column_gap: Regex,
+    number: Regex,
+    latex: Regex,
+}
+
+impl Patterns {
+    fn new() -> Patterns {
+        Patterns {
+            //each match is one code signal
+            code: RegexSet::new([
+                r";\s*$",
+                r"^\s*[{}]|[{}]\s*$",
+                r"^\s*(?:fn|def|class|struct|impl|enum|public|private|protected|static|void|int|float|double|char|bool|boolean|let|const|var|function|import|package|return|using|namespace|try|catch|elif|else)\b",
+                r"^\s*(?:#include|#define|#\[|@\w+)",
+                r"^\s*(?:if|for|while|switch|foreach)\s*\(",
+                //"p->next" but not the arrows of "client -> server"
+                r"::|[\w)\]]->\w|=>|==|!=|&&|\|\||\+\+|\+=|-=|<<|>>",
+                r"\b[a-z_][A-Za-z0-9_]*(?:\.[A-Za-z_]\w*)*!?\((?:[^()]{2,})?\)",
+                r"^\s*(?://|/\*|\*/|#!|<!--|--\s)",
+                r"^\s*(?:SELECT|INSERT INTO|UPDATE|DELETE FROM|CREATE (?:TABLE|DATABASE|VIEW|INDEX)|ALTER TABLE|DROP (?:TABLE|DATABASE)|FROM|WHERE|GROUP BY|ORDER BY|HAVING|VALUES|(?:INNER |LEFT |RIGHT )?JOIN)\b",
+                r"(?i)^\s*(?:select\s+(?:\*|distinct\b|[\w.]+\s*,)|insert\s+into\s+\w+\s*(?:\(|values)|create\s+table\s+\w+\s*\()",
+                r#"^\s*[A-Za-z_][\w.\[\]]*\s*(?:=|:=)\s*(?:[\d"'\[{(-]|\w+\()"#,
+                r"^(?:\t| {4,})\S",
+            ])
+            .unwrap(),
+            numbered: Regex::new(r"^(?:\d{1,2}|[a-z])[.)]\s").unwrap(),
+            table_rule: Regex::new(r"^\|?\s*:?-{3,}:?\s*(?:\|\s*:?-{3,}:?\s*)+\|?$").unwrap(),
+            aligned: Regex::new(r"\S(?: {2,}|\t)\S").unwrap(),
+            column_gap: Regex::new(r" {2,}").unwrap(),
+            number: Regex::new(r"^[-+]?[$€£]?\d[\d,.]*%?$").unwrap(),
+            latex: Regex::new(r"\$\$|\\(?:frac|sum|int|sqrt|prod|alpha|beta|gamma|delta|theta|lambda|sigma|mu|pi|cdot|times|leq|geq|neq)\b|[\^_]\{").unwrap(),
+        }
+    }
+
+    fn is_bullet(&self, trimmed: &str) -> bool {
+        let mut chars = trimmed.chars();
+        let marker: bool = chars.next().is_some_and(|c| BULLETS.contains(&c)) && chars.next().is_some_and(char::is_whitespace);
+        marker || self.numbered.is_match(trimmed)
+    }
+
+    //Relations or math symbols among short tokens: "y = mx + b", "xij = ∑ aik bkj"
+    fn is_formula(&self, trimmed: &str) -> bool {
+        if self.latex.is_match(trimmed) {
+            return true;
+        }
+        if !trimmed.contains(RELATIONS) && !trimmed.contains(MATH_SYMBOLS) {
+            return false;
+        }
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        let words: usize = tokens.iter().filter(|t| t.chars().filter(|c| c.is_alphabetic()).count() >= 4).count();
+        let short: usize = tokens.iter().filter(|t| t.chars().count() <= 3).count();
+        words <= MAX_FORMULA_WORDS && short * 2 >= tokens.len()
+    }
+
+    fn table_line(&self, trimmed: &str) -> TableLine {
+        if self.table_rule.is_match(trimmed) {
+            return TableLine::Cells;
+        }
+        let separated: bool = (trimmed.contains('|') && !trimmed.contains("||")) || trimmed.contains('\t');
+        let cells: usize = self.cells(trimmed).iter().filter(|c| !c.is_empty()).count();
+        if (separated && cells >= 2) || (self.aligned.is_match(trimmed) && cells >= 3) {
+            return TableLine::Cells;
+        }
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        let numbers: usize = tokens.iter().filter(|t| self.number.is_match(t)).count();
+        if numbers >= 2 && numbers * 2 >= tokens.len() {
+            TableLine::Numeric
+        } else {
+            TableLine::No
+        }
+    }
+
+    //Cells of a table row, split the way the row is laid out
+    fn cells(&self, trimmed: &str) -> Vec<String> {
+        let cells: Vec<&str> = if trimmed.contains('|') {
+            trimmed.trim_matches('|').split('|').collect()
+        } else if trimmed.contains('\t') {
+            trimmed.split('\t').collect()
+        } else if self.aligned.is_match(trimmed) {
+            self.column_gap.split(trimmed).collect()
+        } else {
+            trimmed.split_whitespace().collect()
+        };
+        cells.iter().map(|c| c.trim().to_string()).collect()
+    }
+
+    fn line<'a>(&self, text: &'a str, fenced: bool) -> Line<'a> {
+        let trimmed: &str = text.trim();
+        if trimmed.is_empty() {
+            //blank lines inside a fence stay in the listing
+            let kind: Option<BlockKind> = fenced.then_some(BlockKind::Code);
+            return Line { text, kind, fenced, ..Line::blank() };
+        }
+        let bullet: bool = self.is_bullet(trimmed);
+        let fallback: BlockKind = if bullet {
+            BlockKind::Bullet
+        } else if self.is_formula(trimmed) {
+            BlockKind::Math
+        } else {
+            BlockKind::Prose
+        };
+
+        //sentences ending in ';' are not code: a line of three or more mostly plain words loses a signal
+        //("Atomicity: all or nothing;" but not "return count;" or "let total = 5;")
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        let plain_words: usize = tokens
+            .iter()
+            .filter(|t| t.trim_end_matches([',', '.', ':', ';']).chars().all(char::is_alphabetic))
+            .count();
+        let mut code_score: usize = self.code.matches(text).iter().count();
+        if tokens.len() >= 3 && plain_words * 5 >= tokens.len() * 4 {
+            code_score = code_score.saturating_sub(1);
+        }
+
+        let table: TableLine = self.table_line(trimmed);
+        let kind: BlockKind = if fenced {
+            BlockKind::Code
+        } else if table == TableLine::Cells {
+            BlockKind::Table
+        } else if (code_score > 0 && !bullet) || code_score >= MIN_CODE_SCORE {
+            BlockKind::Code
+        } else if table == TableLine::Numeric {
+            BlockKind::Table
+        } else {
+            fallback
+        };
+        Line { text, kind: Some(kind), fallback, code_score, table, fenced }
+    }
+}
+
+//Lines without a code signal between two code lines are part of the listing
+fn fill_code_gaps(lines: &mut [Line]) {
+    let mut last_code: Option<usize> = None;
+    for i in 0..lines.len() {
+        match lines[i].kind {
+            None => last_code = None,
+            Some(BlockKind::Code) => {
+                if let Some(start) = last_code
+                    && i - start - 1 <= MAX_CODE_GAP
+                {
+                    for line in &mut lines[start + 1..i] {
+                        line.kind = Some(BlockKind::Code);
+                    }
+                }
+                last_code = Some(i);
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+//Runs of neighbouring lines of the same kind (line indices); blank lines end a run and a
+//lowercase line right after a bullet continues it
+fn runs(lines: &mut [Line]) -> Vec<(BlockKind, Vec<usize>)> {
+    let mut runs: Vec<(BlockKind, Vec<usize>)> = Vec::new();
+    let mut previous: Option<BlockKind> = None;
+    for (i, line) in lines.iter_mut().enumerate() {
+        let Some(mut kind) = line.kind else {
+            previous = None;
+            continue;
+        };
+        if previous == Some(BlockKind::Bullet) && kind == BlockKind::Prose && line.text.trim_start().starts_with(char::is_lowercase) {
+            kind = BlockKind::Bullet;
+            line.kind = Some(kind);
+        }
+        match runs.last_mut() {
+            Some((run_kind, indices)) if previous == Some(kind) && *run_kind == kind => indices.push(i),
+            _ => runs.push((kind, vec![i])),
+        }
+        previous = Some(kind);
+    }
+    runs
+}
+
+//Is a code or table run really one?
+fn is_confirmed(kind: BlockKind, lines: &[&Line]) -> bool {
+    match kind {
+        BlockKind::Code => lines.iter().any(|l| l.fenced) || lines.iter().map(|l| l.code_score).sum::<usize>() >= MIN_CODE_SCORE,
+        BlockKind::Table => {
+            let cells: usize = lines.iter().filter(|l| l.table == TableLine::Cells).count();
+            cells >= MIN_TABLE_ROWS || lines.len() >= MIN_NUMERIC_ROWS
+        }
+        _ => true,
+    }
+}
+
+//Split one page into blocks of every kind, in reading order
+fn classify_page(patterns: &Patterns, page: u32, text: &str) -> Vec<Block> {
+    let mut fenced: bool = false;
+    let mut lines: Vec<Line> = Vec::new();
+    for line in text.lines() {
+        //``` / ~~~ fences (Markdown, HTML <pre>) mark code explicitly and end the run before them
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            fenced = !fenced;
+            lines.push(Line::blank());
+            continue;
+        }
+        lines.push(patterns.line(line, fenced));
+    }
+    fill_code_gaps(&mut lines);
+
+    //runs that are not code or tables after all go back to prose, bullets or formulas
+    for (kind, indices) in runs(&mut lines) {
+        let run: Vec<&Line> = indices.iter().map(|&i| &lines[i]).collect();
+        if !is_confirmed(kind, &run) {
+            for i in indices {
+                lines[i].kind = Some(lines[i].fallback);
+            }
+        }
+    }
+
+    runs(&mut lines)
+        .into_iter()
+        .map(|(kind, indices)| {
+            let text: String = match kind {
+                BlockKind::Code => indices.iter().map(|&i| lines[i].text.trim_end()).collect::<Vec<&str>>().join("\n"),
+                _ => indices.iter().map(|&i| lines[i].text.trim()).collect::<Vec<&str>>().join("\n"),
+            };
+            let rows: Vec<Vec<String>> = match kind {
+                BlockKind::Table => indices
+                    .iter()
+                    .map(|&i| lines[i].text.trim())
+                    .filter(|line| !patterns.table_rule.is_match(line))
+                    .map(|line| patterns.cells(line))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            Block { kind, page, text, rows }
+        })
+        .collect()
+}
+
+//Classify every page; code, tables and formulas go to `document.blocks` and, unless `keep_text`,
+//out of the page text so the analyzers only see prose and bullets
+pub fn separate_blocks(document: &mut Document, keep_text: bool) {
+    let patterns: Patterns = Patterns::new();
+    document.blocks.clear();
+    for page in &mut document.pages {
+        let (prose, structured): (Vec<Block>, Vec<Block>) = classify_page(&patterns, page.number, &page.text)
+            .into_iter()
+            .partition(|b| matches!(b.kind, BlockKind::Prose | BlockKind::Bullet));
+        if !structured.is_empty() && !keep_text {
+            page.text = prose.iter().map(|b| b.text.as_str()).collect::<Vec<&str>>().join("\n\n");
+        }
+        document.blocks.extend(structured);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::Page;
+
+    //Kind and text of every block of one page
+    fn blocks(text: &str) -> Vec<(BlockKind, String)> {
+        classify_page(&Patterns::new(), 1, text).into_iter().map(|b| (b.kind, b.text)).collect()
+    }
+
+    fn kinds(text: &str) -> Vec<BlockKind> {
+        blocks(text).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    #[test]
+    fn code_listings_are_code() {
+        let listing: &str = "fn main() {\n    let total = 5;\n    println!(\"{}\", total);\n}";
+        assert_eq!(blocks(&format!("The entry point of the program:\n{}", listing)), vec![
+            (BlockKind::Prose, "The entry point of the program:".to_string()),
+            (BlockKind::Code, listing.to_string()),
+        ]);
+        assert_eq!(kinds("SELECT name, grade\nFROM students\nWHERE grade > 50;"), vec![BlockKind::Code]);
+        assert_eq!(kinds("node = node->next;\ncount++;"), vec![BlockKind::Code]);
+    }
+
+    #[test]
+    fn numeric_rows_are_a_table() {
+        let found: Vec<Block> = classify_page(&Patterns::new(), 1, "Enrolment per year:\n2021 45 67.5%\n2022 50 70.1%\n2023 55 72.0%");
+        assert_eq!(found.iter().map(|b| b.kind).collect::<Vec<BlockKind>>(), vec![BlockKind::Prose, BlockKind::Table]);
+        assert_eq!(found[1].rows[0], vec!["2021", "45", "67.5%"]);
+        assert_eq!(found[1].rows.len(), 3);
+    }
+
+    #[test]
+    fn two_numeric_rows_stay_prose() {
+        assert_eq!(kinds("2021 45 67.5%\n2022 50 70.1%"), vec![BlockKind::Prose]);
+    }
+
+    #[test]
+    fn formula_lines_are_math() {
+        assert_eq!(blocks("The line through both points:\ny = mx + b"), vec![
+            (BlockKind::Prose, "The line through both points:".to_string()),
+            (BlockKind::Math, "y = mx + b".to_string()),
+        ]);
+        assert_eq!(kinds("xij = ∑ aik bkj"), vec![BlockKind::Math]);
+        assert_eq!(kinds("\\frac{a}{b} + c"), vec![BlockKind::Math]);
+    }
+
+    #[test]
+    fn arrows_in_prose_stay_prose() {
+        assert_eq!(kinds("A request goes client -> server -> database and back."), vec![BlockKind::Prose]);
+        assert_eq!(kinds("Browser -> web server\nWeb server -> database\nDatabase -> disk"), vec![BlockKind::Prose]);
+    }
+
+    #[test]
+    fn big_o_and_function_names_stay_prose() {
+        assert_eq!(kinds("Merge sort runs in O(n log n) time on every input."), vec![BlockKind::Prose]);
+        assert_eq!(kinds("The function f(x) grows faster than g(x) for large inputs."), vec![BlockKind::Prose]);
+        assert_eq!(kinds("Sorting costs O(n log n)\nSearching costs O(log n)"), vec![BlockKind::Prose]);
+    }
+
+    #[test]
+    fn sentences_ending_in_semicolons_stay_prose() {
+        assert_eq!(kinds("Atomicity: all or nothing;\nDurability: committed data survives;\nIsolation: no interference."), vec![BlockKind::Prose]);
+        assert_eq!(kinds("Keys identify rows; indexes make lookups fast;"), vec![BlockKind::Prose]);
+    }
+
+    #[test]
+    fn bullets_with_code_signals_stay_bullets() {
+        assert_eq!(kinds("- parse the input;\n- build the tree;\n- print the result."), vec![BlockKind::Bullet]);
+    }
+
+    #[test]
+    fn separated_blocks_leave_the_prose_on_the_page() {
+        let text: &str = "Assignments store a value:\n\nlet x = 5;\nlet y = x + 1;\n\nBoth lines end in a semicolon.";
+        let mut document: Document = Document { pages: vec![Page { number: 1, text: text.to_string() }], ..Default::default() };
+        separate_blocks(&mut document, false);
+        assert_eq!(document.pages[0].text, "Assignments store a value:\n\nBoth lines end in a semicolon.");
+        assert_eq!(document.blocks.len(), 1);
+        assert_eq!(document.blocks[0].kind, BlockKind::Code);
+
+        let mut kept: Document = Document { pages: vec![Page { number: 1, text: text.to_string() }], ..Default::default() };
+        separate_blocks(&mut kept, true);
+        assert_eq!(kept.pages[0].text, text);
+        assert_eq!(kept.blocks.len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+//On-disk cache of analysis reports: one JSON file per lecture content and analyzer configuration, so
+//re-running a lecture (or a student uploading the same handout again) skips extraction, the models and
+//the Wikipedia requests. Any setting that changes the report is part of the key
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::config;
+use crate::input;
+use crate::keywords::{KeywordArgs, KeywordMethod};
+use crate::lang::Lang;
+use crate::report::{AnalysisOptions, AnalysisReport, Analyzers};
+
+#[derive(Debug, Clone, Args)]
+pub struct CacheArgs {
+    //Always analyze from scratch (and don't store the result)
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum CacheAction {
+    //Delete every cached report
+    Clear,
+    //Number and size of the cached reports
+    Stats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub directory: PathBuf,
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+    //analyzer settings that are not part of AnalysisOptions (keyword method and limits, model directory)
+    settings: String,
+}
+
+//Everything a cached report depends on, hashed into the file name
+#[derive(Serialize)]
+struct KeyParts<'a> {
+    version: &'a str,
+    content: String,
+    settings: &'a str,
+    layout: bool,
+    keep_blocks: bool,
+    no_clean: bool,
+    pages: Option<String>,
+    section: Option<&'a str>,
+    lang: Lang,
+    extra_stopwords: &'a [String],
+    summary_sentences: usize,
+    section_sentences: usize,
+    summarizer: String,
+    //the summary sentence filter from the config file
+    summary_filter: &'a [String],
+    min_sentence_length: usize,
+    glossary: bool,
+    resources: bool,
+    //which of keywords, summaries and entities run
+    stages: [bool; 3],
+}
+
+impl CacheArgs {
+    //The cache for these analyzer settings, none with --no-cache
+    pub fn cache(&self, keyword_args: Option<&KeywordArgs>, summarization_model: Option<&Path>) -> Option<ResultCache> {
+        if self.no_cache {
+            return None;
+        }
+        let mut settings: String = String::new();
+        if let Some(args) = keyword_args {
+            settings.push_str(&format!("{:?}/{}/{}/{}", args.method, args.top_n(), args.min_phrase_length, args.max_words));
+            //TF-IDF keywords change whenever the course index is rebuilt
+            if args.method == KeywordMethod::Tfidf {
+                let index: Vec<u8> = std::fs::read(args.corpus_index()).unwrap_or_default();
+                settings.push_str(&format!("/{}", input::content_hash(&index)));
+            }
+        }
+        if let Some(dir) = summarization_model {
+            settings.push_str(&format!("/{}", dir.display()));
+        }
+        Some(ResultCache { dir: config::get().cache_dir(), settings })
+    }
+}
+
+impl ResultCache {
+    //Key for a lecture's bytes analyzed with these options
+    pub fn key(&self, bytes: &[u8], options: &AnalysisOptions, analyzers: &Analyzers) -> String {
+        let parts: KeyParts = KeyParts {
+            version: env!("CARGO_PKG_VERSION"),
+            content: input::content_hash(bytes),
+            settings: &self.settings,
+            layout: options.extract_args.layout,
+            keep_blocks: options.extract_args.keep_blocks,
+            no_clean: options.extract_args.clean.no_clean,
+            pages: options.page_args.pages.as_ref().map(|ranges| format!("{:?}", ranges)),
+            section: options.page_args.section.as_deref(),
+            lang: options.stopwords.lang,
+            extra_stopwords: &options.stopwords.extra,
+            summary_sentences: options.summary_sentences,
+            section_sentences: options.section_sentences,
+            summarizer: format!("{:?}", options.summarizer),
+            summary_filter: &config::get().analysis.summary_filter_words,
+            min_sentence_length: config::get().analysis.min_sentence_length,
+            glossary: options.glossary,
+            resources: options.resources,
+            stages: [analyzers.keywords.is_some(), analyzers.summarizer.is_some(), analyzers.entities.is_some()],
+        };
+        input::content_hash(serde_json::to_string(&parts).unwrap_or_default().as_bytes())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    //None when missing or written by an incompatible version (it is then overwritten)
+    pub fn load(&self, key: &str) -> Option<AnalysisReport> {
+        let data: String = std::fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    //Written to a temp file and renamed, so parallel runs never read half a report
+    pub fn store(&self, key: &str, report: &AnalysisReport) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::create_dir_all(&self.dir)?;
+        let file: tempfile::NamedTempFile = tempfile::NamedTempFile::new_in(&self.dir)?;
+        std::fs::write(file.path(), serde_json::to_string(report)?)?;
+        file.persist(self.path(key))?;
+        Ok(())
+    }
+}
+
+fn entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    match std::fs::read_dir(dir) {
+        Ok(read) => Ok(read
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect()),
+        //nothing cached yet
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn stats() -> io::Result<CacheStats> {
+    let dir: PathBuf = config::get().cache_dir();
+    let files: Vec<PathBuf> = entries(&dir)?;
+    let bytes: u64 = files.iter().filter_map(|p| p.metadata().ok()).map(|m| m.len()).sum();
+    Ok(CacheStats { directory: dir, entries: files.len(), bytes })
+}
+
+//Delete every cached report, returns how many there were
+pub fn clear() -> io::Result<CacheStats> {
+    let before: CacheStats = stats()?;
+    for file in entries(&before.directory)? {
+        std::fs::remove_file(file)?;
+    }
+    Ok(before)
+}
@@ -0,0 +1,151 @@
+use clap::Args;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::pdf::Document;
+
+//A line repeated on at least this share of the pages is a header/footer
+const REPEAT_SHARE: f64 = 0.5;
+//...and it has to show up on at least this many pages (short lectures have no real template)
+const MIN_REPEAT_PAGES: usize = 3;
+//Non-empty lines at the top and bottom of a page where headers, footers and page numbers sit
+const EDGE_LINES: usize = 2;
+
+//Cleaning options shared by every subcommand that reads lectures
+#[derive(Debug, Clone, Args)]
+pub struct CleanArgs {
+    //Keep repeated headers/footers and page numbers in the text
+    #[arg(long)]
+    pub no_clean: bool,
+    //Print the lines that were removed as headers/footers/page numbers
+    #[arg(long)]
+    pub show_removed: bool,
+}
+
+//Why a line was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    //same line at the top/bottom of most pages (numbers may differ): course code, instructor, logo text
+    Repeated,
+    //"Page 3 of 40", "3 / 40", "- 3 -", "Slide 3"
+    PageNumber,
+}
+
+//One kind of removed line, text is its first occurrence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedLine {
+    pub text: String,
+    pub reason: RemovalReason,
+    pub pages: Vec<u32>,
+}
+
+//What the cleaning stage took out of a lecture
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanReport {
+    pub removed: Vec<RemovedLine>,
+}
+
+//Lowercase with whitespace collapsed
+fn exact_key(line: &str) -> String {
+    line.to_lowercase().split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+//Like exact_key but numbers replaced by '#', so "SLIDE 2 ..." and "SLIDE 3 ..." match
+fn numbered_key(line: &str) -> String {
+    let mut key: String = String::new();
+    let mut in_number: bool = false;
+    for c in line.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                key.push('#');
+            }
+            in_number = true;
+        } else {
+            key.push(c);
+            in_number = false;
+        }
+    }
+    exact_key(&key)
+}
+
+//Lines of a page, each flagged when it is one of the EDGE_LINES first or last non-empty lines
+fn page_lines(text: &str) -> Vec<(&str, bool)> {
+    let count: usize = text.lines().filter(|l| !l.trim().is_empty()).count();
+    let mut position: usize = 0;
+    text.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                return (line, false);
+            }
+            position += 1;
+            (line, position <= EDGE_LINES || position + EDGE_LINES > count)
+        })
+        .collect()
+}
+
+fn record_page(map: &mut HashMap<String, Vec<u32>>, key: String, page: u32) {
+    if key.is_empty() {
+        return;
+    }
+    let pages: &mut Vec<u32> = map.entry(key).or_default();
+    if pages.last() != Some(&page) {
+        pages.push(page);
+    }
+}
+
+//Remove repeated headers/footers and page-number lines from every page
+pub fn remove_boilerplate(document: &mut Document) -> CleanReport {
+    let page_number = Regex::new(
+        r"(?i)^(?:(?:page|slide|p\.)\s*\d+(?:\s*(?:of|/)\s*\d+)?|\d+\s*(?:of|/)\s*\d+|[-–—]?\s*\d+\s*[-–—]?)$",
+    )
+    .unwrap();
+
+    //pages each top/bottom line shows up on, lines that only differ by their numbers ("SLIDE 2", "SLIDE 3")
+    //counted together. Lines repeated in the body (recurring slide headings like "Example" or "Definition")
+    //are content and stay
+    let mut edge_pages: HashMap<String, Vec<u32>> = HashMap::new();
+    for page in &document.pages {
+        for (line, edge) in page_lines(&page.text) {
+            if edge {
+                record_page(&mut edge_pages, numbered_key(line), page.number);
+            }
+        }
+    }
+    let min_pages: usize = ((document.pages.len() as f64 * REPEAT_SHARE).ceil() as usize).max(MIN_REPEAT_PAGES);
+    let repeated = |key: &str| edge_pages.get(key).is_some_and(|pages| pages.len() >= min_pages);
+
+    let mut report: CleanReport = CleanReport::default();
+    let mut report_index: HashMap<String, usize> = HashMap::new();
+    for page in &mut document.pages {
+        let mut kept: Vec<&str> = Vec::new();
+        for (line, edge) in page_lines(&page.text) {
+            let key: String = numbered_key(line);
+            let reason: Option<RemovalReason> = if key.is_empty() {
+                None
+            } else if edge && page_number.is_match(line.trim()) {
+                Some(RemovalReason::PageNumber)
+            } else if edge && repeated(&key) {
+                Some(RemovalReason::Repeated)
+            } else {
+                None
+            };
+
+            match reason {
+                Some(reason) => {
+                    let i: usize = *report_index.entry(key).or_insert_with(|| {
+                        report.removed.push(RemovedLine { text: line.trim().to_string(), reason, pages: Vec::new() });
+                        report.removed.len() - 1
+                    });
+                    let removed: &mut RemovedLine = &mut report.removed[i];
+                    if removed.pages.last() != Some(&page.number) {
+                        removed.pages.push(page.number);
+                    }
+                }
+                None => kept.push(line),
+            }
+        }
+        page.text = kept.join("\n").trim().to_string();
+    }
+    report
+}
@@ -0,0 +1,306 @@
+//Settings that used to be hard-coded (keyword limit, summary filter words, fonts, output directory, server address),
+//read once at startup. Each layer overrides the one before it:
+//built-in defaults < user file (~/.config/lecture-analyzer/config.toml) < project file (./lecture-analyzer.toml)
+//< LECTURE_ANALYZER_<SECTION>_<KEY> environment variables < command line flags.
+//Files may be TOML or JSON and only need the keys they change
+use clap::Subcommand;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+//Project file names looked up in the working directory (and "config.toml"/"config.json" in the user directory)
+const PROJECT_FILES: &[&str] = &["lecture-analyzer.toml", "lecture-analyzer.json"];
+const USER_FILES: &[&str] = &["config.toml", "config.json"];
+const ENV_PREFIX: &str = "LECTURE_ANALYZER";
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigAction {
+    //Print the effective configuration (all layers merged) and where it came from
+    Show,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub analysis: AnalysisConfig,
+    pub export: ExportConfig,
+    pub output: OutputConfig,
+    pub server: ServerConfig,
+    //files and variables the values were read from, in order (not part of the file format)
+    #[serde(skip)]
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalysisConfig {
+    //default for --top-n
+    pub top_n: usize,
+    //summary sentences containing any of these (lowercase) are skipped: legal notes, reading lists, links
+    pub summary_filter_words: Vec<String>,
+    //shorter sentences are skipped too, in bytes
+    pub min_sentence_length: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportConfig {
+    //TrueType fonts of the summary PDF
+    pub regular_font: PathBuf,
+    pub bold_font: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    //summary PDFs, the corpus index, batch and watch reports and the result cache go under this directory
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    //defaults for serve --bind and --port
+    pub bind: IpAddr,
+    pub port: u16,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        let words: &[&str] = &["copyright", "right reserved", "legal", "resources", "published", "www", "http"];
+        AnalysisConfig {
+            top_n: 10,
+            summary_filter_words: words.iter().map(|w| w.to_string()).collect(),
+            min_sentence_length: 20,
+        }
+    }
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        ExportConfig {
+            regular_font: PathBuf::from("fonts/roboto/Roboto-Regular.ttf"),
+            bold_font: PathBuf::from("fonts/roboto/Roboto-Bold.ttf"),
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig { dir: PathBuf::from("output") }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig { bind: IpAddr::from([127, 0, 0, 1]), port: 8080 }
+    }
+}
+
+//The loaded configuration; the built-in defaults when `load` was not called
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+//A config file or environment variable that could not be used
+#[derive(Clone, thiserror::Error)]
+#[error("invalid config {layer}: {message} (`config show` lists every layer)")]
+pub struct LayerError {
+    //file path or variable name
+    pub layer: String,
+    pub message: String,
+}
+
+//main prints returned errors with Debug: show the message, not the struct
+impl std::fmt::Debug for LayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+//Read every layer and make it the configuration for this run (after the command line is parsed: flags
+//left out are resolved against it). The first broken layer is the error, named by its file or variable
+pub fn load() -> Result<&'static Config, LayerError> {
+    let (config, mut failed) = Config::load();
+    if !failed.is_empty() {
+        return Err(failed.remove(0));
+    }
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+//Like `load`, but broken layers are left out and returned instead, so `config show` can say which failed
+pub fn load_partial() -> (&'static Config, Vec<LayerError>) {
+    let (config, failed) = Config::load();
+    (CONFIG.get_or_init(|| config), failed)
+}
+
+//$XDG_CONFIG_HOME/lecture-analyzer, or ~/.config/lecture-analyzer
+fn user_dir() -> Option<PathBuf> {
+    let base: PathBuf = match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("lecture-analyzer"))
+}
+
+//A config file as JSON values, whatever its format
+fn read_file(path: &Path) -> Result<Value, Box<dyn Error + Send + Sync>> {
+    let text: String = std::fs::read_to_string(path)?;
+    let value: Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&text)?,
+        _ => toml::from_str(&text)?,
+    };
+    Ok(value)
+}
+
+//Keys of `layer` replace those of `base`, tables are merged key by key
+fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+//An environment value in the type of the setting it replaces: lists are comma separated
+fn env_value(current: &Value, raw: &str) -> Result<Value, String> {
+    match current {
+        Value::Number(_) => raw.trim().parse::<u64>().map(Value::from).map_err(|e| e.to_string()),
+        Value::Array(_) => Ok(Value::Array(
+            raw.split(',').map(|w| w.trim()).filter(|w| !w.is_empty()).map(|w| Value::String(w.to_string())).collect(),
+        )),
+        _ => Ok(Value::String(raw.to_string())),
+    }
+}
+
+//`layer` applied on top of `merged`, kept only when the result is still a valid configuration
+//(parses, no unknown keys, values of the right type)
+fn apply_layer(merged: &mut Value, layer: impl FnOnce(&mut Value) -> Result<(), String>) -> Result<(), String> {
+    let mut candidate: Value = merged.clone();
+    layer(&mut candidate)?;
+    serde_json::from_value::<Config>(candidate.clone()).map_err(|e| e.to_string())?;
+    *merged = candidate;
+    Ok(())
+}
+
+impl Config {
+    //Every layer in order, each checked on its own so an error points at the file or variable it came from;
+    //broken layers are skipped and returned
+    fn load() -> (Config, Vec<LayerError>) {
+        let mut merged: Value = serde_json::to_value(Config::default()).unwrap_or_default();
+        let mut sources: Vec<String> = vec!["defaults".to_string()];
+        let mut failed: Vec<LayerError> = Vec::new();
+        let mut record = |layer: String, result: Result<(), String>| match result {
+            Ok(()) => sources.push(layer),
+            Err(message) => failed.push(LayerError { layer, message: message.trim_end().to_string() }),
+        };
+
+        let user_files: Vec<PathBuf> = user_dir().map(|dir| USER_FILES.iter().map(|f| dir.join(f)).collect()).unwrap_or_default();
+        let project_files: Vec<PathBuf> = PROJECT_FILES.iter().map(PathBuf::from).collect();
+        for path in user_files.iter().chain(&project_files).filter(|p| p.is_file()) {
+            let result: Result<(), String> = apply_layer(&mut merged, |value| {
+                merge(value, read_file(path).map_err(|e| e.to_string())?);
+                Ok(())
+            });
+            record(path.display().to_string(), result);
+        }
+
+        //LECTURE_ANALYZER_ANALYSIS_TOP_N=20, LECTURE_ANALYZER_SERVER_PORT=3000...
+        let keys: Vec<(String, String)> = match &merged {
+            Value::Object(sections) => sections
+                .iter()
+                .filter_map(|(section, values)| Some((section, values.as_object()?)))
+                .flat_map(|(section, values)| values.keys().map(move |key| (section.clone(), key.clone())))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (section, key) in keys {
+            let name: String = format!("{}_{}_{}", ENV_PREFIX, section, key).to_uppercase();
+            if let Ok(raw) = std::env::var(&name) {
+                let result: Result<(), String> = apply_layer(&mut merged, |value| {
+                    let slot: &mut Value = &mut value[&section][&key];
+                    *slot = env_value(slot, &raw)?;
+                    Ok(())
+                });
+                record(name, result);
+            }
+        }
+
+        //every layer kept was checked, so this only falls back to the defaults if the defaults themselves are broken
+        let mut config: Config = serde_json::from_value(merged).unwrap_or_default();
+        config.sources = sources;
+        (config, failed)
+    }
+
+    //Where the result cache lives
+    pub fn cache_dir(&self) -> PathBuf {
+        self.output.dir.join("cache")
+    }
+
+    //Default location of the TF-IDF corpus index
+    pub fn corpus_index(&self) -> PathBuf {
+        self.output.dir.join("corpus_index.json")
+    }
+
+    //The configuration as a TOML file (what `config show` prints): every key, so it can be copied and edited
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> Value {
+        serde_json::to_value(Config::default()).unwrap()
+    }
+
+    fn file_layer(text: &str) -> impl FnOnce(&mut Value) -> Result<(), String> + '_ {
+        move |value| {
+            merge(value, toml::from_str(text).map_err(|e: toml::de::Error| e.to_string())?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn valid_layers_override_only_their_keys() {
+        let mut merged: Value = defaults();
+        apply_layer(&mut merged, file_layer("[analysis]\ntop_n = 25\n")).unwrap();
+        let config: Config = serde_json::from_value(merged).unwrap();
+        assert_eq!(config.analysis.top_n, 25);
+        assert_eq!(config.analysis.min_sentence_length, 20);
+        assert_eq!(config.server.port, 8080);
+    }
+
+    #[test]
+    fn broken_layers_are_rejected_and_leave_the_rest() {
+        let mut merged: Value = defaults();
+        apply_layer(&mut merged, file_layer("[server]\nport = 3000\n")).unwrap();
+        for broken in ["[analysis]\ntop_n = \"ten\"\n", "[server]\nprot = 1\n", "[analysis\n"] {
+            assert!(apply_layer(&mut merged, file_layer(broken)).is_err(), "{:?} should be rejected", broken);
+        }
+        let config: Config = serde_json::from_value(merged).unwrap();
+        assert_eq!((config.analysis.top_n, config.server.port), (10, 3000));
+    }
+
+    #[test]
+    fn environment_values_take_the_type_of_the_setting() {
+        assert_eq!(env_value(&Value::from(10), " 20 "), Ok(Value::from(20)));
+        assert!(env_value(&Value::from(10), "twenty").is_err());
+        assert_eq!(env_value(&serde_json::json!(["a"]), "www, http,"), Ok(serde_json::json!(["www", "http"])));
+    }
+}
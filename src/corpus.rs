@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+use crate::analyze::tokenize_words;
+use crate::lang::{StopwordConfig, StopwordList};
+use crate::input;
+use crate::pdf::{ExtractArgs, PageArgs};
+use crate::report;
+
+//Document frequencies for a course: how many lectures mention each word
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CorpusIndex {
+    pub documents: usize,
+    pub document_frequency: HashMap<String, usize>,
+}
+
+impl CorpusIndex {
+    //Count one lecture (each word once, however often it appears)
+    pub fn add_document(&mut self, text:&str, stopwords:&StopwordList) {
+        let words: HashSet<String> = tokenize_words(text, stopwords).into_iter().collect();
+        for word in words {
+            *self.document_frequency.entry(word).or_insert(0) += 1;
+        }
+        self.documents += 1;
+    }
+
+    //Smoothed inverse document frequency, words unseen in the course score highest
+    pub fn idf(&self, word:&str) -> f64 {
+        let df: usize = self.document_frequency.get(word).copied().unwrap_or(0);
+        ((self.documents as f64 + 1.0) / (df as f64 + 1.0)).ln() + 1.0
+    }
+
+    pub fn save(&self, path:&Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path:&Path) -> Result<CorpusIndex, Box<dyn Error + Send + Sync>> {
+        let data: String = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read corpus index {} (run the index subcommand first): {}", path.display(), e))?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
+
+//Index every lecture (PDF, .pptx, .md, .html, .txt) in a directory (cleaned like a single lecture); unreadable files are reported and skipped
+pub fn build_index(dir:&Path, stopwords:&StopwordConfig, extract_args:&ExtractArgs) -> Result<CorpusIndex, Box<dyn Error + Send + Sync>> {
+    let mut index: CorpusIndex = CorpusIndex::default();
+    for path in input::lectures_in(dir)? {
+        match report::load_document(&path.to_string_lossy(), extract_args, &PageArgs::default()) {
+            Ok((document, _)) => {
+                eprintln!("Indexed {}", path.display());
+                let text: String = document.text();
+                index.add_document(&text, &stopwords.for_text(&text));
+            }
+            Err(e) => eprintln!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    Ok(index)
+}
+
+//Rank a lecture's words by TF-IDF against the course: frequent here, rare elsewhere
+pub fn rank_tfidf(text:&str, index:&CorpusIndex, top_n: usize, stopwords:&StopwordList) -> Vec<(String, f64)> {
+    let words: Vec<String> = tokenize_words(text, stopwords);
+    let total: f64 = words.len().max(1) as f64;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in words {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+
+    let mut scored: Vec<(String, f64)> = counts
+        .into_iter()
+        .map(|(word, count)| {
+            let score: f64 = (count as f64 / total) * index.idf(&word);
+            (word, score)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+    scored.truncate(top_n);
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::Lang;
+
+    fn course() -> (CorpusIndex, StopwordList) {
+        let stopwords: StopwordList = StopwordList::new(Lang::English, &[]);
+        let mut index: CorpusIndex = CorpusIndex::default();
+        index.add_document("The database stores tables. Normalization removes redundancy.", &stopwords);
+        index.add_document("Every database needs indexes on its tables.", &stopwords);
+        index.add_document("A database runs transactions on tables.", &stopwords);
+        (index, stopwords)
+    }
+
+    #[test]
+    fn counts_each_word_once_per_lecture() {
+        let (index, stopwords) = course();
+        assert_eq!(index.documents, 3);
+        assert_eq!(index.document_frequency["database"], 3);
+        assert_eq!(index.document_frequency["normalization"], 1);
+        assert!(!index.document_frequency.contains_key("the"));
+
+        let mut repeated: CorpusIndex = CorpusIndex::default();
+        repeated.add_document("database database database", &stopwords);
+        assert_eq!(repeated.document_frequency["database"], 1);
+    }
+
+    #[test]
+    fn words_in_every_lecture_rank_below_specific_ones() {
+        let (index, stopwords) = course();
+        assert!(index.idf("database") < index.idf("normalization"));
+        assert!(index.idf("normalization") < index.idf("sharding"));
+
+        let ranked: Vec<(String, f64)> = rank_tfidf("Normalization of a database, database tables and normalization.", &index, 3, &stopwords);
+        let words: Vec<&str> = ranked.iter().map(|(word, _)| word.as_str()).collect();
+        assert_eq!(words, vec!["normalization", "database", "tables"]);
+    }
+
+    #[test]
+    fn saves_and_loads_the_index() {
+        let (index, stopwords) = course();
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = dir.path().join("course").join("corpus_index.json");
+        index.save(&path).unwrap();
+
+        let loaded: CorpusIndex = CorpusIndex::load(&path).unwrap();
+        assert_eq!(loaded.documents, index.documents);
+        assert_eq!(loaded.document_frequency, index.document_frequency);
+        let text: &str = "Normalization keeps database tables small.";
+        assert_eq!(rank_tfidf(text, &loaded, 5, &stopwords), rank_tfidf(text, &index, 5, &stopwords));
+    }
+
+    #[test]
+    fn loading_a_missing_index_says_how_to_build_one() {
+        let error: String = CorpusIndex::load(Path::new("/nonexistent/corpus_index.json")).unwrap_err().to_string();
+        assert!(error.contains("run the index subcommand first"));
+    }
+}
@@ -2,8 +2,10 @@ use printpdf::*;
 use std::fs::File;
 use ::std::io::BufWriter;
 use std::path::{Path, PathBuf};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use textwrap::wrap;
+use crate::config::{self, Config};
+use crate::report::AnalysisReport;
 
 
 const PAGE_HEIGHT:f64 = 297.0;
@@ -56,12 +58,21 @@ fn add_spacing(
     ( new_y, layer)
 }
 
+//Lay out the same report the CLI prints and the server returns, saved as output_path inside out_dir
 pub fn export_summary_to_pdf(
+    out_dir:&Path,
     output_path:&str,
-    summary: &str,
-    keywords:&[String],
-    resources:&[String],
+    report:&AnalysisReport,
 ) ->Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let details: Vec<String> = report.detail_lines();
+    let outline: Vec<String> = report.outline_lines();
+    let keywords: Vec<String> = report.keyword_lines();
+    let glossary: Vec<String> = report.glossary_lines();
+    let undefined: Vec<&str> = report.undefined_terms();
+    let summary: String = report.summary_lines().join("\n");
+    let sections: Vec<String> = report.section_lines();
+    let blocks: Vec<String> = report.block_lines();
+    let resources: Vec<String> = report.resource_lines();
     let (doc, page1, layer1) = PdfDocument::new("Lecture Summary", Mm(210.0), Mm(PAGE_HEIGHT), "Layer 1"); // A4 size
     let mut layer = doc.get_page(page1).get_layer(layer1);
 
@@ -73,9 +84,10 @@ pub fn export_summary_to_pdf(
     let bullet_font_size: f64 = 11.0;
     let line_height: f64 = 14.0; // line height in mm
 
-    //different fonts for title, sections and bullet text(might add other fonts later)
-    let font_pdf: IndirectFontRef = doc.add_external_font(File::open("fonts/roboto/Roboto-Regular.ttf")?)?;
-    let font_bold_pdf: IndirectFontRef = doc.add_external_font(File::open("fonts/roboto/Roboto-Bold.ttf")?)?;
+    //different fonts for title, sections and bullet text(might add other fonts later), set in the config file
+    let settings: &Config = config::get();
+    let font_pdf: IndirectFontRef = doc.add_external_font(File::open(&settings.export.regular_font).with_context(|| format!("cannot open font {}", settings.export.regular_font.display()))?)?;
+    let font_bold_pdf: IndirectFontRef = doc.add_external_font(File::open(&settings.export.bold_font).with_context(|| format!("cannot open font {}", settings.export.bold_font.display()))?)?;
 
     //Title
     layer.use_text("Lecture Summary", title_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
@@ -83,6 +95,32 @@ pub fn export_summary_to_pdf(
     current_y = y;
     layer = layer_;
 
+    //Document details (title, author, date, pages) from the PDF metadata
+    for line in &details {
+        let (y,layer_) = draw_wrapped_text(&doc, layer, line, &font_pdf, bullet_font_size, start_x, current_y, 80, line_height);
+        current_y = y;
+        layer = layer_;
+    }
+    let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
+    current_y = y;
+    layer = layer_;
+
+    //Outline section (only when the PDF has bookmarks), entries come indented by level
+    if !outline.is_empty() {
+        layer.use_text("Outline:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
+        current_y = y;
+        layer = layer_;
+        for entry in &outline {
+            let (y,layer_) = draw_wrapped_text(&doc, layer, entry, &font_pdf, bullet_font_size, start_x + 5.0, current_y, 80, line_height);
+            current_y = y;
+            layer = layer_;
+        }
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 2.0);
+        current_y = y;
+        layer = layer_;
+    }
+
     //Keywords Section
     layer.use_text("Keywords:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
     let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
@@ -90,7 +128,7 @@ pub fn export_summary_to_pdf(
     layer = layer_;
 
     //iterate over keyword and print as a separate bullet point
-    for kw in keywords {
+    for kw in &keywords {
         let bullet: String = format!("• {}", kw);
         layer.use_text(&bullet, bullet_font_size, Mm(start_x + 5.0), Mm(current_y), &font_pdf);
         current_y -= line_height;
@@ -105,6 +143,28 @@ pub fn export_summary_to_pdf(
     current_y = y;
     layer = layer_;
 
+    //Glossary section: a definition per keyword, then the keywords the lecture never defines
+    if !report.glossary.is_empty() {
+        layer.use_text("Glossary:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
+        current_y = y;
+        layer = layer_;
+        for entry in &glossary {
+            let (y,layer_) = draw_wrapped_text(&doc, layer, &format!("• {}", entry), &font_pdf, bullet_font_size, start_x + 5.0, current_y, 80, line_height);
+            current_y = y;
+            layer = layer_;
+        }
+        if !undefined.is_empty() {
+            let line: String = format!("Not defined in the lecture: {}", undefined.join(", "));
+            let (y,layer_) = draw_wrapped_text(&doc, layer, &line, &font_pdf, bullet_font_size, start_x + 5.0, current_y, 80, line_height);
+            current_y = y;
+            layer = layer_;
+        }
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 2.0);
+        current_y = y;
+        layer = layer_;
+    }
+
     //Summary section
     layer.use_text("Summary:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
     let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
@@ -113,7 +173,7 @@ pub fn export_summary_to_pdf(
     let (y,layer_) = draw_wrapped_text(
         &doc,
         layer,
-        summary,
+        &summary,
         &font_pdf,
         bullet_font_size,
         start_x + 5.0,
@@ -127,13 +187,45 @@ pub fn export_summary_to_pdf(
     current_y = y;
     layer = layer_;
 
+    //Section summaries (one paragraph per outline section)
+    if !sections.is_empty() {
+        layer.use_text("Section Summaries:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
+        current_y = y;
+        layer = layer_;
+        for section in &sections {
+            let (y,layer_) = draw_wrapped_text(&doc, layer, &format!("• {}", section), &font_pdf, bullet_font_size, start_x + 5.0, current_y, 80, line_height);
+            current_y = y;
+            layer = layer_;
+        }
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 2.0);
+        current_y = y;
+        layer = layer_;
+    }
+
+    //Code, tables and formulas kept out of the analysis: "Code (page 5):" headers with their lines indented
+    if !blocks.is_empty() {
+        layer.use_text("Code, Tables and Formulas:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
+        current_y = y;
+        layer = layer_;
+        for line in &blocks {
+            let (y,layer_) = draw_wrapped_text(&doc, layer, line, &font_pdf, bullet_font_size, start_x + 5.0, current_y, 80, line_height);
+            current_y = y;
+            layer = layer_;
+        }
+        let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 2.0);
+        current_y = y;
+        layer = layer_;
+    }
+
     //Resources Section
     layer.use_text("Resources:", section_font_size, Mm(start_x), Mm(current_y), &font_bold_pdf);
     let (y,layer_) = add_spacing(&doc, layer, current_y, line_height, 1.0);
     current_y = y;
     layer = layer_;
 
-    for r in resources {
+    for r in &resources {
         let (y,layer_) =draw_wrapped_text(
             &doc,
             layer,
@@ -151,10 +243,9 @@ pub fn export_summary_to_pdf(
 
 
     
-    //Save PDF(Ensure output path is user/project directory)
-    let safe_dir: &Path = Path::new("output");
-    std::fs::create_dir_all(safe_dir)?; //create output directory if not exists
-    let safe_path: PathBuf = safe_dir.join(output_path);
+    //Save PDF(Ensure output path is user/project directory, the directory the caller picked)
+    std::fs::create_dir_all(out_dir)?; //create output directory if not exists
+    let safe_path: PathBuf = out_dir.join(output_path);
     let file: File = File::create(&safe_path)?;
     let mut buf_writer: BufWriter<File> = BufWriter::new(file);
     doc.save(&mut buf_writer)?;
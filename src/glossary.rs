@@ -0,0 +1,247 @@
+//Glossary: for every keyword, the sentence of the lecture that defines it ("X is a...", "X refers to...",
+//"we define X as...", "X: ..." on slides). When several sentences do, the most explicit phrasing of a
+//reasonable length wins; keywords the lecture never defines are kept without a definition so they can be flagged
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::pdf::Document;
+use crate::segment;
+
+//Longer "sentences" are paragraphs the segmenter could not split, not definitions
+const MAX_DEFINITION_LENGTH: usize = 400;
+//Definitions in this range (bytes) are preferred over terse or rambling ones
+const PREFERRED_LENGTH: std::ops::RangeInclusive<usize> = 30..=250;
+//"X is ..." without an article only counts when the next word can start a definition
+//("Information is processed data", not "Normalization is important")...
+const NOT_DEFINITIONAL: &[&str] = &[
+    "not", "also", "still", "only", "just", "always", "never", "often", "usually", "now", "then", "here", "there",
+    "very", "so", "too", "more", "less", "most", "being", "important", "useful", "used", "shown", "given", "needed",
+    "required", "possible", "available", "easy", "hard", "difficult", "same", "similar", "different", "covered",
+    "discussed", "explained", "in", "on", "at", "to", "by", "for", "of", "from", "with", "about", "like",
+];
+//...and starts a noun phrase: a bare adjective ends the sentence ("The cache is fast.") or is followed by one
+//of these ("Memory is cheap and plentiful", "SQL is easier than C", "Indexing is worth it")
+const PREDICATE_LINKS: &[&str] = &[
+    "and", "or", "but", "than", "as", "to", "enough", "because", "when", "if", "so", "compared", "in", "on", "at",
+    "by", "for", "of", "from", "with", "it",
+];
+
+//Sentence shape a definition was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    //"we define X as...", "X is defined as..."
+    DefinedAs,
+    //"X refers to...", "X means...", "X stands for..."
+    RefersTo,
+    //"... is called X", "... is known as X" (X ending the name, not "is called X networks")
+    Called,
+    //"X: ..." or "X - ..." (slide bullets)
+    Colon,
+    //"X is a...", "X are ..."
+    IsA,
+}
+
+//One keyword of the lecture with its best definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    //the defining sentence, none when the lecture never defines the term
+    pub definition: Option<String>,
+    pub page: Option<u32>,
+    pub kind: Option<DefinitionKind>,
+}
+
+//The definition patterns of one term, most explicit first
+struct TermPatterns {
+    patterns: Vec<(DefinitionKind, Regex)>,
+}
+
+//The term as a pattern: case-insensitive, singular or plural ("database" finds "Databases are..."),
+//any whitespace between the words of a keyphrase
+fn term_pattern(term: &str) -> String {
+    let term: String = term.trim().to_lowercase();
+    let mut variants: Vec<String> = vec![term.clone()];
+    match term.strip_suffix('s') {
+        Some(singular) if singular.len() > 2 => variants.push(singular.to_string()),
+        Some(_) => {}
+        None => variants.push(format!("{}s", term)),
+    }
+    let escaped: Vec<String> = variants
+        .iter()
+        .map(|v| v.split_whitespace().map(regex::escape).collect::<Vec<String>>().join(r"\s+"))
+        .collect();
+    format!(r"\b(?:{})\b", escaped.join("|"))
+}
+
+impl TermPatterns {
+    fn new(term: &str) -> Option<Self> {
+        let t: String = term_pattern(term);
+        //the term at the start of the sentence, maybe after an article or as the abbreviation of a longer name
+        //("A Database Management System (DBMS) is...")
+        let subject: String = format!(r"^(?:(?:an?|the)\s+)?(?:{t}|[^()]+?\(\s*{t}\s*\))");
+        //an aside between the term and its verb: "(DBMS)" or ", also called a database server,"
+        //(unbounded: counted repetitions make the patterns slow to build)
+        let aside: &str = r"(?:\s*\([^)]+\)|\s*,[^,]+,)?";
+        let sources: [(DefinitionKind, String); 6] = [
+            (DefinitionKind::DefinedAs, format!(r"\bwe\s+(?:can\s+|will\s+)?define\s+(?:an?\s+|the\s+)?{t}\s+as\b")),
+            (DefinitionKind::DefinedAs, format!(r"{subject}{aside}\s+(?:is|are|can\s+be|will\s+be)\s+defined\s+as\b")),
+            (DefinitionKind::RefersTo, format!(r"{subject}{aside}\s+(?:refers?\s+to|means|stands?\s+for|denotes?)\b")),
+            (DefinitionKind::Called, format!(r#"\b(?:is|are)\s+(?:called|known\s+as|referred\s+to\s+as|termed)\s+(?:an?\s+|the\s+)?["“']?{t}["”']?\s*(?:[.,;:(]|$)"#)),
+            (DefinitionKind::Colon, format!(r"{subject}\s*(?::|\s[–—-])\s*\S")),
+            (DefinitionKind::IsA, format!(r"{subject}{aside}\s+(?:is|are)\s+(?P<next>\w+)(?:\s+(?P<after>\w+))?")),
+        ];
+        let patterns: Option<Vec<(DefinitionKind, Regex)>> = sources
+            .into_iter()
+            .map(|(kind, source)| Regex::new(&format!("(?i){}", source)).ok().map(|re| (kind, re)))
+            .collect();
+        patterns.map(|patterns| TermPatterns { patterns })
+    }
+
+    //How the sentence defines the term, if it does, and how much it is worth (higher is better)
+    fn score(&self, sentence: &str) -> Option<(DefinitionKind, usize)> {
+        for (kind, re) in &self.patterns {
+            let Some(caps) = re.captures(sentence) else { continue };
+            let weight: usize = match kind {
+                DefinitionKind::DefinedAs => 5,
+                DefinitionKind::RefersTo | DefinitionKind::Called => 4,
+                DefinitionKind::Colon => 3,
+                DefinitionKind::IsA => {
+                    let word = |name: &str| caps.name(name).map(|m| m.as_str().to_lowercase());
+                    let next: String = word("next").unwrap_or_default();
+                    if ["a", "an", "the", "one", "any"].contains(&next.as_str()) {
+                        3
+                    } else if NOT_DEFINITIONAL.contains(&next.as_str()) {
+                        continue;
+                    } else {
+                        match word("after") {
+                            Some(after) if !PREDICATE_LINKS.contains(&after.as_str()) => 2,
+                            _ => continue,
+                        }
+                    }
+                }
+            };
+            let length_bonus: usize = usize::from(PREFERRED_LENGTH.contains(&sentence.len()));
+            return Some((*kind, weight + length_bonus));
+        }
+        None
+    }
+}
+
+//Sentences that could define something, with their page: bullet markers stripped by the segmenter, questions
+//("What is a DBMS?") and run-on paragraphs left out
+fn candidate_sentences(document: &Document) -> Vec<(String, u32)> {
+    document
+        .pages
+        .iter()
+        .flat_map(|page| segment::segment(&page.text).into_iter().map(move |s| (s.text, page.number)))
+        .map(|(text, page)| (text.trim().to_string(), page))
+        .filter(|(text, _)| !text.is_empty() && !text.ends_with('?') && text.len() <= MAX_DEFINITION_LENGTH)
+        .collect()
+}
+
+//A glossary entry per term, in the order given (the keyword ranking); the first of equally good definitions wins
+pub fn build_glossary(document: &Document, terms: &[String]) -> Vec<GlossaryEntry> {
+    let sentences: Vec<(String, u32)> = candidate_sentences(document);
+    terms
+        .iter()
+        .map(|term| {
+            let mut best: Option<(&str, u32, DefinitionKind, usize)> = None;
+            if let Some(patterns) = TermPatterns::new(term) {
+                for (text, page) in &sentences {
+                    if let Some((kind, score)) = patterns.score(text)
+                        && best.is_none_or(|(_, _, _, best_score)| score > best_score)
+                    {
+                        best = Some((text, *page, kind, score));
+                    }
+                }
+            }
+            GlossaryEntry {
+                term: term.clone(),
+                definition: best.map(|(text, ..)| text.to_string()),
+                page: best.map(|(_, page, ..)| page),
+                kind: best.map(|(_, _, kind, _)| kind),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::Page;
+
+    fn document(pages: &[&str]) -> Document {
+        let pages: Vec<Page> = pages.iter().enumerate().map(|(i, text)| Page { number: i as u32 + 1, text: text.to_string() }).collect();
+        Document { pages, ..Default::default() }
+    }
+
+    //The definition found for one term and how it was phrased
+    fn define(pages: &[&str], term: &str) -> Option<(String, DefinitionKind)> {
+        let entry: GlossaryEntry = build_glossary(&document(pages), &[term.to_string()]).remove(0);
+        entry.definition.zip(entry.kind)
+    }
+
+    fn kind(text: &str, term: &str) -> Option<DefinitionKind> {
+        define(&[text], term).map(|(_, kind)| kind)
+    }
+
+    #[test]
+    fn finds_every_definition_pattern() {
+        assert_eq!(kind("We define a transaction as a unit of work that either commits or aborts.", "transaction"), Some(DefinitionKind::DefinedAs));
+        assert_eq!(kind("Normalization is defined as removing redundancy from a schema.", "normalization"), Some(DefinitionKind::DefinedAs));
+        assert_eq!(kind("Concurrency refers to several transactions running at the same time.", "concurrency"), Some(DefinitionKind::RefersTo));
+        assert_eq!(kind("A column that identifies each row is called the primary key.", "primary key"), Some(DefinitionKind::Called));
+        assert_eq!(kind("• Index: a structure that speeds up lookups on a column", "index"), Some(DefinitionKind::Colon));
+        assert_eq!(kind("A database is an organized collection of related data.", "database"), Some(DefinitionKind::IsA));
+        assert_eq!(kind("Information is processed data that has meaning.", "information"), Some(DefinitionKind::IsA));
+    }
+
+    #[test]
+    fn matches_abbreviations_asides_and_plurals() {
+        let text: &str = "A Database Management System (DBMS) is software that manages databases.";
+        assert_eq!(kind(text, "dbms"), Some(DefinitionKind::IsA));
+        assert_eq!(kind("A view, also called a virtual table, is the result of a stored query.", "view"), Some(DefinitionKind::IsA));
+        assert_eq!(kind("Databases are organized collections of data.", "database"), Some(DefinitionKind::IsA));
+        assert_eq!(kind("A key is a set of columns.", "keys"), Some(DefinitionKind::IsA));
+    }
+
+    #[test]
+    fn predicative_adjectives_are_not_definitions() {
+        assert_eq!(kind("The cache is fast.", "cache"), None);
+        assert_eq!(kind("Memory is cheap.", "memory"), None);
+        assert_eq!(kind("Memory is cheap and plentiful these days.", "memory"), None);
+        assert_eq!(kind("SQL is easier than writing the loops by hand.", "sql"), None);
+        assert_eq!(kind("Normalization is important for every schema.", "normalization"), None);
+        assert_eq!(kind("The cache is not a database.", "cache"), None);
+    }
+
+    #[test]
+    fn skips_questions_and_called_with_a_longer_name() {
+        assert_eq!(kind("Is a join a combination of tables?", "join"), None);
+        assert_eq!(kind("A join is a combination of two tables?", "join"), None);
+        assert_eq!(kind("These models are called neural networks.", "neural"), None);
+    }
+
+    #[test]
+    fn the_most_explicit_definition_wins() {
+        let pages: &[&str] = &[
+            "A key is a column.",
+            "We define a key as a minimal set of columns that identifies every row of a table.",
+        ];
+        let entry: GlossaryEntry = build_glossary(&document(pages), &["key".to_string()]).remove(0);
+        assert_eq!(entry.kind, Some(DefinitionKind::DefinedAs));
+        assert_eq!(entry.page, Some(2));
+        assert!(entry.definition.unwrap().starts_with("We define a key"));
+    }
+
+    #[test]
+    fn keeps_undefined_terms_in_order() {
+        let pages: &[&str] = &["A relation is a table of rows. Sharding splits data across machines."];
+        let terms: Vec<String> = vec!["sharding".to_string(), "relation".to_string()];
+        let glossary: Vec<GlossaryEntry> = build_glossary(&document(pages), &terms);
+        assert_eq!(glossary.iter().map(|e| e.term.as_str()).collect::<Vec<&str>>(), vec!["sharding", "relation"]);
+        assert_eq!((glossary[0].definition.as_deref(), glossary[0].page, glossary[0].kind), (None, None, None));
+        assert_eq!(glossary[1].definition.as_deref(), Some("A relation is a table of rows."));
+        assert_eq!(glossary[1].page, Some(1));
+    }
+}
@@ -0,0 +1,507 @@
+//Lecture input formats (PDF, .pptx, Markdown, HTML, plain text): every reader produces the same pdf::Document (pages, metadata,
+//outline) so cleaning, normalization and the analyzers don't care where the text came from.
+//Slides are pages for .pptx; Markdown and HTML start a new page at every level 1-2 heading.
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::pdf::{self, Document, ExtractError, Metadata, OutlineEntry, Page, PageArgs};
+
+//Supported lecture formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Pdf,
+    Pptx,
+    Markdown,
+    Html,
+    Text,
+}
+
+//File extensions we read (used by `index`, `batch` and `watch` to pick lecture files from a directory)
+pub const EXTENSIONS: &[&str] = &["pdf", "pptx", "md", "markdown", "html", "htm", "txt"];
+
+//Headings at this level or above start a new page in Markdown/HTML lectures
+const PAGE_HEADING_LEVEL: usize = 2;
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+//Lecture files directly inside a directory (not its subdirectories), sorted by name
+pub fn lectures_in(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_supported(p))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+//SHA-256 of a lecture's bytes as hex: same content, same hash, whatever the file is called
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+//Magic bytes first (a PDF saved as .txt is still a PDF), then the extension, then a look at the text
+pub fn detect_format(path: &Path, bytes: &[u8]) -> Option<InputFormat> {
+    if bytes.starts_with(b"%PDF") {
+        return Some(InputFormat::Pdf);
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        return Some(InputFormat::Pptx);
+    }
+    let extension: String = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "pdf" => return Some(InputFormat::Pdf),
+        "pptx" => return Some(InputFormat::Pptx),
+        "md" | "markdown" => return Some(InputFormat::Markdown),
+        "html" | "htm" => return Some(InputFormat::Html),
+        "txt" => return Some(InputFormat::Text),
+        _ => {}
+    }
+    //anything else must at least be text
+    let text: &str = std::str::from_utf8(bytes).ok().filter(|t| !t.contains('\0'))?;
+    let start: String = text.trim_start().chars().take(100).collect::<String>().to_lowercase();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        Some(InputFormat::Html)
+    } else {
+        Some(InputFormat::Text)
+    }
+}
+
+//Read a lecture in any supported format; PDFs go through the panic/timeout-guarded pdf extractor
+//(`layout` only applies to them), the other formats are read whole and then cut down to the selected pages
+pub fn extract_document(path: &str, timeout: Option<Duration>, layout: bool, selection: &PageArgs) -> Result<Document, ExtractError> {
+    let bytes: Vec<u8> = std::fs::read(path)?;
+    let format: InputFormat = detect_format(Path::new(path), &bytes)
+        .ok_or_else(|| ExtractError::Format(format!("unsupported file type (expected one of: {})", EXTENSIONS.join(", "))))?;
+    let document: Document = match format {
+        InputFormat::Pdf => return pdf::extract_document(path, timeout, layout, selection),
+        InputFormat::Pptx => read_pptx(&bytes)?,
+        InputFormat::Markdown => read_markdown(&String::from_utf8_lossy(&bytes)),
+        InputFormat::Html => read_html(&String::from_utf8_lossy(&bytes)),
+        InputFormat::Text => read_text(&String::from_utf8_lossy(&bytes)),
+    };
+    document.select(selection)
+}
+
+//Pages in order plus the outline; page_count follows the pages
+fn build_document(pages: Vec<Page>, mut metadata: Metadata, outline: Vec<OutlineEntry>) -> Document {
+    metadata.page_count = pages.len();
+    Document { pages, metadata, outline, blocks: Vec::new() }
+}
+
+//&amp; &lt; &#233; ... in XML/HTML text
+fn unescape_xml(text: &str) -> String {
+    let entity = Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|amp|lt|gt|quot|apos);").unwrap();
+    entity
+        .replace_all(text, |caps: &regex::Captures| {
+            let name: &str = &caps[1];
+            let decoded: Option<char> = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ if name.starts_with("#x") => u32::from_str_radix(&name[2..], 16).ok().and_then(char::from_u32),
+                _ => name[1..].parse::<u32>().ok().and_then(char::from_u32),
+            };
+            decoded.map(String::from).unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+//---- PowerPoint (.pptx): a zip of XML parts, one ppt/slides/slideN.xml per slide, listed in ppt/presentation.xml ----
+
+fn zip_entry(archive: &mut zip::ZipArchive<std::io::Cursor<&[u8]>>, name: &str) -> Option<String> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut xml: String = String::new();
+    file.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+//Paragraphs (<a:p>) of a shape or slide, each the concatenation of its text runs (<a:t>)
+fn pptx_paragraphs(xml: &str) -> Vec<String> {
+    let run = Regex::new(r"(?s)<a:t(?:\s[^>]*)?>(.*?)</a:t>|<a:br/>").unwrap();
+    xml.split("</a:p>")
+        .map(|paragraph| {
+            run.captures_iter(paragraph)
+                .map(|caps| caps.get(1).map(|m| unescape_xml(m.as_str())).unwrap_or_else(|| " ".to_string()))
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+//Text of a slide: paragraphs one per line, table rows (<a:tbl>) as "cell | cell"
+fn pptx_lines(slide_xml: &str) -> Vec<String> {
+    let table = Regex::new(r"(?s)<a:tbl>.*?</a:tbl>").unwrap();
+    let mut lines: Vec<String> = Vec::new();
+    let mut last: usize = 0;
+    for found in table.find_iter(slide_xml) {
+        lines.extend(pptx_paragraphs(&slide_xml[last..found.start()]));
+        for row in found.as_str().split("</a:tr>") {
+            let cells: Vec<String> = row.split("</a:tc>").map(|cell| pptx_paragraphs(cell).join(" ")).collect();
+            if cells.iter().any(|c| !c.is_empty()) {
+                lines.push(cells[..cells.len() - 1].join(" | "));
+            }
+        }
+        last = found.end();
+    }
+    lines.extend(pptx_paragraphs(&slide_xml[last..]));
+    lines
+}
+
+//Title placeholder text of a slide, if it has one
+fn pptx_title(slide_xml: &str) -> Option<String> {
+    slide_xml
+        .split("</p:sp>")
+        .find(|shape| shape.contains(r#"type="title""#) || shape.contains(r#"type="ctrTitle""#))
+        .map(|shape| pptx_paragraphs(shape).join(" "))
+        .filter(|title| !title.is_empty())
+}
+
+//Slide parts in presentation order: the slide list of ppt/presentation.xml (<p:sldId r:id="rId2"/>) resolved
+//through ppt/_rels/presentation.xml.rels. The number in "slideN.xml" is only the order the slides were
+//created in, so it is the fallback for archives without a usable slide list
+fn pptx_slide_order(archive: &mut zip::ZipArchive<std::io::Cursor<&[u8]>>) -> Vec<String> {
+    let presentation: String = zip_entry(archive, "ppt/presentation.xml").unwrap_or_default();
+    let relationships: String = zip_entry(archive, "ppt/_rels/presentation.xml.rels").unwrap_or_default();
+    let slide_id = Regex::new(r#"<p:sldId\s[^>]*\br:id="([^"]+)""#).unwrap();
+    let relationship = Regex::new(r"<Relationship\s[^>]*>").unwrap();
+    let attribute = |tag: &str, name: &str| {
+        Regex::new(&format!(r#"\s{}="([^"]*)""#, name)).unwrap().captures(tag).map(|caps| unescape_xml(&caps[1]))
+    };
+    //relationship id -> part name, targets are relative to ppt/ unless they start at the root
+    let targets: Vec<(String, String)> = relationship
+        .find_iter(&relationships)
+        .filter_map(|tag| {
+            let target: String = attribute(tag.as_str(), "Target")?;
+            let part: String = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("ppt/{}", target),
+            };
+            Some((attribute(tag.as_str(), "Id")?, part))
+        })
+        .collect();
+    let ordered: Vec<String> = slide_id
+        .captures_iter(&presentation)
+        .filter_map(|caps| targets.iter().find(|(id, _)| *id == caps[1]).map(|(_, part)| part.clone()))
+        .filter(|part| archive.file_names().any(|name| name == part))
+        .collect();
+    if !ordered.is_empty() {
+        return ordered;
+    }
+
+    let slide_name = Regex::new(r"^ppt/slides/slide(\d+)\.xml$").unwrap();
+    let mut slides: Vec<(u32, String)> = archive
+        .file_names()
+        .filter_map(|name| Some((slide_name.captures(name)?[1].parse::<u32>().ok()?, name.to_string())))
+        .collect();
+    slides.sort();
+    slides.into_iter().map(|(_, name)| name).collect()
+}
+
+//"2024-02-19T10:15:00Z" -> "2024-02-19 10:15:00"
+fn format_iso_date(date: &str) -> String {
+    date.trim().trim_end_matches('Z').replacen('T', " ", 1)
+}
+
+fn read_pptx(bytes: &[u8]) -> Result<Document, ExtractError> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| ExtractError::Format(format!("not a readable .pptx: {}", e)))?;
+
+    let slides: Vec<String> = pptx_slide_order(&mut archive);
+    if slides.is_empty() {
+        return Err(ExtractError::Format("not a PowerPoint file: no slides found".to_string()));
+    }
+
+    let mut pages: Vec<Page> = Vec::new();
+    let mut outline: Vec<OutlineEntry> = Vec::new();
+    for (number, name) in (1..).zip(slides) {
+        let xml: String = zip_entry(&mut archive, &name).unwrap_or_default();
+        if let Some(title) = pptx_title(&xml) {
+            outline.push(OutlineEntry { level: 1, title, page: number });
+        }
+        pages.push(Page { number, text: pptx_lines(&xml).join("\n") });
+    }
+
+    //Dublin Core properties in docProps/core.xml
+    let core: String = zip_entry(&mut archive, "docProps/core.xml").unwrap_or_default();
+    let property = |tag: &str| {
+        Regex::new(&format!(r"(?s)<{}[^>]*>(.*?)</{}>", tag, tag))
+            .unwrap()
+            .captures(&core)
+            .map(|caps| unescape_xml(caps[1].trim()))
+            .filter(|value| !value.is_empty())
+    };
+    let metadata: Metadata = Metadata {
+        title: property("dc:title"),
+        author: property("dc:creator"),
+        subject: property("dc:subject"),
+        creation_date: property("dcterms:created").map(|d| format_iso_date(&d)),
+        page_count: 0,
+    };
+    Ok(build_document(pages, metadata, outline))
+}
+
+//---- Markdown (.md) ----
+
+//Markup removed from one line: links/images keep their text, emphasis and code marks go
+fn strip_markdown_inline(line: &str) -> String {
+    let image = Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap();
+    let link = Regex::new(r"\[([^\]]+)\]\([^)]*\)").unwrap();
+    let emphasis = Regex::new(r"\*\*|__|~~|`").unwrap();
+    let star_emphasis = Regex::new(r"\*([^*\s][^*]*)\*").unwrap();
+    let html_tag = Regex::new(r"</?[a-zA-Z][^>]*>").unwrap();
+
+    let line: String = image.replace_all(line, "$1").to_string();
+    let line: String = link.replace_all(&line, "$1").to_string();
+    let line: String = html_tag.replace_all(&line, "").to_string();
+    let line: String = emphasis.replace_all(&line, "").to_string();
+    star_emphasis.replace_all(&line, "$1").to_string()
+}
+
+fn read_markdown(text: &str) -> Document {
+    let heading = Regex::new(r"^(#{1,6})\s+(.*?)\s*#*\s*$").unwrap();
+    let bullet = Regex::new(r"^\s*[-*+]\s+").unwrap();
+    let rule = Regex::new(r"^\s*([-*_]\s*){3,}$").unwrap();
+
+    let mut pages: Vec<Page> = Vec::new();
+    let mut outline: Vec<OutlineEntry> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_code: bool = false;
+    let mut title: Option<String> = None;
+
+    //YAML front matter (--- ... ---) at the very top is not lecture text
+    let mut body: Vec<&str> = text.lines().collect();
+    if body.first().is_some_and(|l| l.trim() == "---")
+        && let Some(end) = body.iter().skip(1).position(|l| l.trim() == "---")
+    {
+        body.drain(..end + 2);
+    }
+
+    for line in body {
+        //fences stay so the block classifier knows the lines between them are code
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_code = !in_code;
+            lines.push("```".to_string());
+            continue;
+        }
+        if in_code {
+            lines.push(line.to_string());
+            continue;
+        }
+        if rule.is_match(line) {
+            continue;
+        }
+        if let Some(caps) = heading.captures(line) {
+            let level: usize = caps[1].len();
+            let text: String = strip_markdown_inline(&caps[2]);
+            if level <= PAGE_HEADING_LEVEL && lines.iter().any(|l| !l.trim().is_empty()) {
+                pages.push(Page { number: pages.len() as u32 + 1, text: lines.join("\n").trim().to_string() });
+                lines.clear();
+            }
+            if level == 1 && title.is_none() {
+                title = Some(text.clone());
+            }
+            outline.push(OutlineEntry { level, title: text.clone(), page: pages.len() as u32 + 1 });
+            //blank lines keep the heading a sentence of its own
+            lines.push(String::new());
+            lines.push(text);
+            lines.push(String::new());
+            continue;
+        }
+        let line: String = bullet.replace(line, "• ").to_string();
+        let line: &str = line.trim_start().trim_start_matches('>').trim_start();
+        lines.push(strip_markdown_inline(line));
+    }
+    if lines.iter().any(|l| !l.trim().is_empty()) {
+        pages.push(Page { number: pages.len() as u32 + 1, text: lines.join("\n").trim().to_string() });
+    }
+    build_document(pages, Metadata { title, ..Default::default() }, outline)
+}
+
+//---- HTML (.html, saved web pages) ----
+
+//Elements whose text is kept, each as its own line (nested ones are covered by their parent);
+//a table row is one line with its cells separated by " | "
+const HTML_BLOCKS: &[&str] = &[
+    "h1", "h2", "h3", "h4", "h5", "h6", "p", "li", "pre", "blockquote", "tr", "dt", "dd", "figcaption", "caption",
+];
+
+fn read_html(text: &str) -> Document {
+    let html: Html = Html::parse_document(text);
+    let blocks: Selector = Selector::parse(&HTML_BLOCKS.join(", ")).unwrap();
+    let cell_selector: Selector = Selector::parse("td, th").unwrap();
+    let title_selector: Selector = Selector::parse("title").unwrap();
+    let meta_selector: Selector = Selector::parse("meta[name]").unwrap();
+
+    let mut pages: Vec<Page> = Vec::new();
+    let mut outline: Vec<OutlineEntry> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+
+    for element in html.select(&blocks) {
+        let nested: bool = element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| HTML_BLOCKS.contains(&a.value().name()));
+        let hidden: bool = element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| matches!(a.value().name(), "script" | "style" | "nav" | "noscript"));
+        if nested || hidden {
+            continue;
+        }
+        let name: &str = element.value().name();
+        let content: String = element.text().collect::<String>();
+        let content: String = if name == "pre" {
+            format!("```\n{}\n```", content.trim_matches('\n'))
+        } else if name == "tr" {
+            element
+                .select(&cell_selector)
+                .map(|cell| cell.text().collect::<String>().split_whitespace().collect::<Vec<&str>>().join(" "))
+                .collect::<Vec<String>>()
+                .join(" | ")
+                .trim_matches(|c: char| c == '|' || c == ' ')
+                .to_string()
+        } else {
+            content.split_whitespace().collect::<Vec<&str>>().join(" ")
+        };
+        if content.is_empty() {
+            continue;
+        }
+
+        match name.strip_prefix('h').and_then(|level| level.parse::<usize>().ok()) {
+            Some(level) => {
+                if level <= PAGE_HEADING_LEVEL && !lines.is_empty() {
+                    pages.push(Page { number: pages.len() as u32 + 1, text: lines.join("\n").trim().to_string() });
+                    lines.clear();
+                }
+                outline.push(OutlineEntry { level, title: content.clone(), page: pages.len() as u32 + 1 });
+                lines.push(format!("\n{}\n", content));
+            }
+            None if name == "li" => lines.push(format!("• {}", content)),
+            None => lines.push(content),
+        }
+    }
+    if !lines.is_empty() {
+        pages.push(Page { number: pages.len() as u32 + 1, text: lines.join("\n").trim().to_string() });
+    }
+
+    let meta = |name: &str| {
+        html.select(&meta_selector)
+            .find(|m| m.value().attr("name").is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .and_then(|m| m.value().attr("content"))
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+    };
+    let metadata: Metadata = Metadata {
+        title: html
+            .select(&title_selector)
+            .next()
+            .map(|t| t.text().collect::<String>().trim().to_string())
+            .filter(|t| !t.is_empty()),
+        author: meta("author"),
+        subject: meta("description"),
+        creation_date: meta("date"),
+        page_count: 0,
+    };
+    build_document(pages, metadata, outline)
+}
+
+//---- Plain text (.txt): form feeds separate pages ----
+
+fn read_text(text: &str) -> Document {
+    let pages: Vec<Page> = text
+        .split('\u{c}')
+        .enumerate()
+        .map(|(i, page)| Page { number: i as u32 + 1, text: page.trim().to_string() })
+        .collect();
+    build_document(pages, Metadata::default(), Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    //A slide with a title placeholder and one body paragraph
+    fn slide(title: &str, body: &str) -> String {
+        format!(
+            r#"<p:sld><p:cSld><p:spTree><p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:txBody></p:sp><p:sp><p:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#,
+            title, body
+        )
+    }
+
+    fn pptx(parts: &[(&str, String)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, xml) in parts {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(xml.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    //Three slides moved around after they were created: slide3.xml is shown first, slide1.xml last
+    fn reordered_deck(with_slide_list: bool) -> Vec<u8> {
+        let mut parts: Vec<(&str, String)> = vec![
+            ("ppt/slides/slide1.xml", slide("Summary", "What we covered today.")),
+            ("ppt/slides/slide2.xml", slide("Joins", "Joins combine tables.")),
+            ("ppt/slides/slide3.xml", slide("Introduction", "Tables hold rows.")),
+        ];
+        if with_slide_list {
+            parts.push((
+                "ppt/presentation.xml",
+                r#"<p:presentation><p:sldIdLst><p:sldId id="256" r:id="rId9"/><p:sldId r:id="rId7" id="257"/><p:sldId id="258" r:id="rId8"/></p:sldIdLst></p:presentation>"#.to_string(),
+            ));
+            parts.push((
+                "ppt/_rels/presentation.xml.rels",
+                concat!(
+                    r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideMaster" Target="slideMasters/slideMaster1.xml"/>"#,
+                    r#"<Relationship Id="rId7" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide2.xml"/>"#,
+                    r#"<Relationship Target="/ppt/slides/slide1.xml" Id="rId8" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide"/>"#,
+                    r#"<Relationship Id="rId9" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide3.xml"/></Relationships>"#,
+                )
+                .to_string(),
+            ));
+        }
+        pptx(&parts)
+    }
+
+    #[test]
+    fn pptx_slides_follow_the_presentation_order() {
+        let document: Document = read_pptx(&reordered_deck(true)).unwrap();
+        let pages: Vec<(u32, &str)> = document.pages.iter().map(|p| (p.number, p.text.as_str())).collect();
+        assert_eq!(
+            pages,
+            vec![
+                (1, "Introduction\nTables hold rows."),
+                (2, "Joins\nJoins combine tables."),
+                (3, "Summary\nWhat we covered today."),
+            ]
+        );
+        let outline: Vec<(&str, u32)> = document.outline.iter().map(|e| (e.title.as_str(), e.page)).collect();
+        assert_eq!(outline, vec![("Introduction", 1), ("Joins", 2), ("Summary", 3)]);
+    }
+
+    #[test]
+    fn pptx_without_a_slide_list_uses_the_file_names() {
+        let document: Document = read_pptx(&reordered_deck(false)).unwrap();
+        let titles: Vec<&str> = document.outline.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Summary", "Joins", "Introduction"]);
+    }
+
+    #[test]
+    fn pptx_without_slides_is_rejected() {
+        let bytes: Vec<u8> = pptx(&[("docProps/core.xml", "<cp:coreProperties/>".to_string())]);
+        assert!(matches!(read_pptx(&bytes), Err(ExtractError::Format(_))));
+    }
+}
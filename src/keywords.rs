@@ -0,0 +1,159 @@
+use clap::{Args, ValueEnum};
+use std::error::Error;
+use std::path::PathBuf;
+use crate::analyze::{self, RakeOptions};
+use crate::config;
+use crate::corpus::{self, CorpusIndex};
+use crate::lang::{LanguageArgs, StopwordList};
+use crate::ner::{self, NERModel};
+use crate::pdf::Document;
+
+//How keywords are picked; only `ner` needs the model download and libtorch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum KeywordMethod {
+    //named entities from the NER model
+    Ner,
+    //most frequent non-stopwords (offline, no model)
+    #[default]
+    Frequency,
+    //multi-word keyphrases scored with RAKE (offline, no model)
+    Rake,
+    //words distinctive for this lecture compared to the course (needs an index built with `index`)
+    Tfidf,
+}
+
+//Keyword options shared by every subcommand that needs keywords
+#[derive(Debug, Clone, Args)]
+pub struct KeywordArgs {
+    //ner (named entities), frequency (single words), rake (multi-word keyphrases) or tfidf (against a course index)
+    #[arg(long, value_enum, default_value_t = KeywordMethod::Frequency)]
+    pub method: KeywordMethod,
+    //Keywords kept (default from the config file, 10)
+    #[arg(long)]
+    pub top_n: Option<usize>,
+    //RAKE only: shortest keyphrase kept, in characters
+    #[arg(long, default_value_t = 3)]
+    pub min_phrase_length: usize,
+    //RAKE only: longest keyphrase kept, in words
+    #[arg(long, default_value_t = 3)]
+    pub max_words: usize,
+    //TF-IDF only: document frequencies written by the `index` subcommand (default <output dir>/corpus_index.json)
+    #[arg(long)]
+    pub corpus_index: Option<PathBuf>,
+    #[command(flatten)]
+    pub language: LanguageArgs,
+}
+
+//Flags left out fall back to the configuration, which is only read after the command line is parsed
+impl KeywordArgs {
+    pub fn top_n(&self) -> usize {
+        self.top_n.unwrap_or(config::get().analysis.top_n)
+    }
+
+    pub fn corpus_index(&self) -> PathBuf {
+        self.corpus_index.clone().unwrap_or_else(|| config::get().corpus_index())
+    }
+}
+
+//Anything that can pick keywords from a lecture (new methods implement this), best first with its score
+//(stopwords are resolved per lecture by the caller; the NER extractor ignores them)
+pub trait KeywordExtractor: Send {
+    fn extract(&mut self, document: &Document, stopwords: &StopwordList) -> Vec<(String, f64)>;
+    //Method name and version shown in reports
+    fn name(&self) -> &'static str;
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+}
+
+//Single-word frequency ranking
+pub struct FrequencyExtractor {
+    pub top_n: usize,
+}
+
+impl KeywordExtractor for FrequencyExtractor {
+    fn extract(&mut self, document: &Document, stopwords: &StopwordList) -> Vec<(String, f64)> {
+        analyze::extract_keywords(&document.text(), self.top_n, stopwords)
+    }
+
+    fn name(&self) -> &'static str {
+        "frequency"
+    }
+}
+
+//RAKE keyphrases
+pub struct RakeExtractor {
+    pub options: RakeOptions,
+}
+
+impl KeywordExtractor for RakeExtractor {
+    fn extract(&mut self, document: &Document, stopwords: &StopwordList) -> Vec<(String, f64)> {
+        analyze::extract_keyphrases_rake(&document.text(), &self.options, stopwords)
+    }
+
+    fn name(&self) -> &'static str {
+        "rake"
+    }
+}
+
+//TF-IDF ranking against a course corpus index
+pub struct TfidfExtractor {
+    pub index: CorpusIndex,
+    pub top_n: usize,
+}
+
+impl KeywordExtractor for TfidfExtractor {
+    fn extract(&mut self, document: &Document, stopwords: &StopwordList) -> Vec<(String, f64)> {
+        corpus::rank_tfidf(&document.text(), &self.index, self.top_n, stopwords)
+    }
+
+    fn name(&self) -> &'static str {
+        "tfidf"
+    }
+}
+
+//Named entities from the (chunked, aggregated) NER pipeline
+pub struct NerExtractor {
+    pub model: NERModel,
+    pub top_n: usize,
+}
+
+impl KeywordExtractor for NerExtractor {
+    fn extract(&mut self, document: &Document, _stopwords: &StopwordList) -> Vec<(String, f64)> {
+        analyze::extract_keywords_ner(&mut self.model, document)
+            .into_iter()
+            .take(self.top_n)
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "ner"
+    }
+
+    fn version(&self) -> String {
+        ner::MODEL_VERSION.to_string()
+    }
+}
+
+//Build the extractor picked on the CLI/server flags; the NER model is only loaded for `ner`
+pub fn build_extractor(args: &KeywordArgs) -> Result<Box<dyn KeywordExtractor>, Box<dyn Error + Send + Sync>> {
+    let extractor: Box<dyn KeywordExtractor> = match args.method {
+        KeywordMethod::Ner => Box::new(NerExtractor {
+            model: ner::load_model()?,
+            top_n: args.top_n(),
+        }),
+        KeywordMethod::Frequency => Box::new(FrequencyExtractor { top_n: args.top_n() }),
+        KeywordMethod::Rake => Box::new(RakeExtractor {
+            options: RakeOptions {
+                top_n: args.top_n(),
+                min_phrase_length: args.min_phrase_length,
+                max_words: args.max_words,
+            },
+        }),
+        KeywordMethod::Tfidf => Box::new(TfidfExtractor {
+            index: CorpusIndex::load(&args.corpus_index())?,
+            top_n: args.top_n(),
+        }),
+    };
+    Ok(extractor)
+}
@@ -0,0 +1,200 @@
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::PathBuf;
+use stopwords::{Language, Stopwords, NLTK};
+
+//Lecture language; `auto` guesses it from which stopword list matches the text best
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    Auto,
+    #[value(alias = "en")]
+    English,
+    #[value(alias = "ms")]
+    Malay,
+    #[value(alias = "de")]
+    German,
+}
+
+//Language options shared by every subcommand that tokenizes text
+#[derive(Debug, Clone, Args)]
+pub struct LanguageArgs {
+    //auto, english (en), malay (ms) or german (de)
+    #[arg(long, value_enum, default_value_t = Lang::Auto)]
+    pub lang: Lang,
+    //Extra stopwords for domain noise words: one per line, '#' starts a comment
+    #[arg(long)]
+    pub stopwords_file: Option<PathBuf>,
+}
+
+//Our original English list (contractions included), merged with NLTK English
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "a","about","above","after","again","against","all","am","an","and","any","are",
+    "aren't","as","at","be","because","been","before","being","below","between","both",
+    "but","by","can't","cannot","could","couldn't","did","didn't","do","does","doesn't",
+    "doing","don't","down","during","each","few","for","from","further","had","hadn't","has",
+    "hasn't","have","haven't","having","he","he'd","he'll","he's","her","here","here's","hers",
+    "herself","him","himself","his","how","how's","i","i'd","i'll","i'm","i've","if","in","into","is",
+    "isn't","it","it's","its","itself","just","ll","may","me","mightn't","more","most","mustn't","my",
+    "myself","needn't","no","nor","not","now","of","off","on","once","only","or","other","our","ours",
+    "ourselves","out","over","own","re","s","same","shan't","she","she'd","she'll","she's","should",
+    "shouldn't","so","some","such","t","than","that","that's","the","their","theirs","them","themselves","then",
+    "there","there's","these","they","they'd","they'll","they're","they've","this","those","through","to","too","under","until",
+    "up","ve","very","was","wasn't","we","we'd","we'll","we're","we've","were","weren't","what","what's","when","when's",
+    "where","where's","which","while","who","who's","whom","why","why's","will","with","won't","would","wouldn't","y","you","you'd","you'll",
+    "you're","you've","your","yours","yourself","yourselves"
+];
+
+//The stopwords crate has no Malay list, so keep a basic one here
+const MALAY_STOPWORDS: &[&str] = &[
+    "ada","adalah","agar","akan","aku","anda","antara","apa","apabila","atau","bagaimana","bagi",
+    "bahawa","bahkan","banyak","beberapa","belum","berikut","bersama","boleh","dalam","dan","dapat",
+    "dari","daripada","demikian","dengan","di","dia","hanya","hingga","ia","iaitu","ialah","ini",
+    "itu","jika","juga","kami","kamu","kata","ke","kepada","kerana","ketika","kita","lagi","lain",
+    "maka","mana","manakala","masih","mereka","namun","oleh","pada","para","pula","saja","sahaja",
+    "sama","sangat","satu","sebagai","sebelum","secara","sedang","sehingga","sejak","selain",
+    "selepas","semua","seperti","serta","setiap","sini","situ","suatu","sudah","supaya","tanpa",
+    "tentang","terdapat","tersebut","tetapi","tidak","untuk","yang",
+];
+
+//Lowercase stopwords for one language plus the user's extra words
+pub struct StopwordList {
+    pub language: Lang,
+    words: HashSet<String>,
+}
+
+impl StopwordList {
+    pub fn new(language: Lang, extra: &[String]) -> Self {
+        let mut words: HashSet<String> = builtin_stopwords(language).into_iter().collect();
+        words.extend(extra.iter().map(|w| w.to_lowercase()));
+        StopwordList { language, words }
+    }
+
+    pub fn contains(&self, word:&str) -> bool {
+        self.words.contains(word)
+    }
+}
+
+//Stopwords of one language (Auto falls back to English)
+fn builtin_stopwords(language: Lang) -> Vec<String> {
+    let words: Vec<&str> = match language {
+        Lang::Auto | Lang::English => ENGLISH_STOPWORDS
+            .iter()
+            .chain(NLTK::stopwords(Language::English).unwrap_or_default())
+            .cloned()
+            .collect(),
+        Lang::Malay => MALAY_STOPWORDS.to_vec(),
+        Lang::German => NLTK::stopwords(Language::German).unwrap_or_default().to_vec(),
+    };
+    words.into_iter().map(|w| w.to_string()).collect()
+}
+
+//Guess the language by counting words that are stopwords in each candidate language
+pub fn detect_language(text:&str) -> Lang {
+    let candidates: [Lang; 3] = [Lang::English, Lang::Malay, Lang::German];
+    let lists: Vec<HashSet<String>> = candidates.iter().map(|&l| builtin_stopwords(l).into_iter().collect()).collect();
+    let mut hits: [usize; 3] = [0; 3];
+    for word in text.split(|c:char| !c.is_alphabetic()).filter(|w| !w.is_empty()) {
+        let word: String = word.to_lowercase();
+        for (i, list) in lists.iter().enumerate() {
+            if list.contains(&word) {
+                hits[i] += 1;
+            }
+        }
+    }
+    //English wins ties (and texts with no stopwords at all)
+    let mut best: usize = 0;
+    for i in 1..candidates.len() {
+        if hits[i] > hits[best] {
+            best = i;
+        }
+    }
+    candidates[best]
+}
+
+//Stopword settings resolved once (extra file read up front), applied per lecture
+#[derive(Debug, Clone, Default)]
+pub struct StopwordConfig {
+    pub lang: Lang,
+    pub extra: Vec<String>,
+}
+
+impl StopwordConfig {
+    //Stopwords for this lecture (detects the language when set to auto)
+    pub fn for_text(&self, text:&str) -> StopwordList {
+        let language: Lang = match self.lang {
+            Lang::Auto => detect_language(text),
+            lang => lang,
+        };
+        StopwordList::new(language, &self.extra)
+    }
+}
+
+impl LanguageArgs {
+    pub fn config(&self) -> Result<StopwordConfig, Box<dyn Error + Send + Sync>> {
+        let extra: Vec<String> = match &self.stopwords_file {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read stopwords file {}: {}", path.display(), e))?
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim().to_string())
+                .filter(|w| !w.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok(StopwordConfig { lang: self.lang, extra })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn detects_english_malay_and_german() {
+        assert_eq!(detect_language("A database is a collection of data that is stored in tables and managed by a system."), Lang::English);
+        assert_eq!(detect_language("Pangkalan data ialah koleksi data yang disimpan dalam jadual dan diurus oleh sistem."), Lang::Malay);
+        assert_eq!(detect_language("Eine Datenbank ist eine Sammlung von Daten, die in Tabellen gespeichert und von einem System verwaltet wird."), Lang::German);
+    }
+
+    #[test]
+    fn english_wins_ties() {
+        assert_eq!(detect_language("Database normalization"), Lang::English);
+        assert_eq!(detect_language(""), Lang::English);
+        //one English and one Malay stopword
+        assert_eq!(detect_language("the dan"), Lang::English);
+    }
+
+    #[test]
+    fn auto_picks_the_list_of_the_detected_language() {
+        let config: StopwordConfig = StopwordConfig { lang: Lang::Auto, extra: Vec::new() };
+        let list: StopwordList = config.for_text("Pangkalan data ialah koleksi data yang disimpan dalam jadual.");
+        assert_eq!(list.language, Lang::Malay);
+        assert!(list.contains("yang"));
+
+        let config: StopwordConfig = StopwordConfig { lang: Lang::German, extra: Vec::new() };
+        assert_eq!(config.for_text("The database stores tables.").language, Lang::German);
+    }
+
+    #[test]
+    fn reads_extra_stopwords_with_comments() {
+        let mut file: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "# domain noise\nslide\n  Lecture  # course name\n\n#\nweek\n").unwrap();
+        let args: LanguageArgs = LanguageArgs { lang: Lang::English, stopwords_file: Some(file.path().to_path_buf()) };
+
+        let config: StopwordConfig = args.config().unwrap();
+        assert_eq!(config.extra, vec!["slide", "Lecture", "week"]);
+        let list: StopwordList = config.for_text("");
+        assert!(list.contains("lecture") && list.contains("slide") && list.contains("the"));
+        assert!(!list.contains("course"));
+    }
+
+    #[test]
+    fn missing_stopwords_file_is_an_error() {
+        let args: LanguageArgs = LanguageArgs { lang: Lang::Auto, stopwords_file: Some(PathBuf::from("/nonexistent/stopwords.txt")) };
+        assert!(args.config().unwrap_err().to_string().contains("cannot read stopwords file"));
+    }
+}
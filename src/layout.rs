@@ -0,0 +1,203 @@
+//Layout-aware reading order for multi-column handouts and papers. pdf-extract writes text in
+//content stream order, which jumps between columns on the same baseline; here every glyph is
+//collected with its position, glyphs are grouped into line segments, gutters between columns are
+//found from the segments' x extents and each column is read top to bottom, left to right.
+use pdf_extract::{ConvertToFmt, MediaBox, OutputDev, OutputError, Transform};
+use std::fmt::Write;
+
+//Glyphs closer than this share of the font size vertically sit on the same baseline
+const BASELINE_TOLERANCE: f64 = 0.5;
+//A horizontal gap wider than this share of the font size is a space between words
+const WORD_GAP: f64 = 0.1;
+//...and wider than this splits the line into segments (a column gutter or a table cell)
+const SEGMENT_GAP: f64 = 1.5;
+//A gutter needs at least this many segments on each side
+const MIN_COLUMN_LINES: usize = 3;
+//Narrowest gap between columns that counts as a gutter, in points
+const MIN_GUTTER_WIDTH: f64 = 6.0;
+//Vertical gap between lines (in line heights) that starts a new paragraph
+const PARAGRAPH_GAP: f64 = 1.8;
+
+//One glyph, y measured from the top of the page
+#[derive(Debug, Clone)]
+struct Glyph {
+    x: f64,
+    y: f64,
+    end: f64,
+    size: f64,
+    text: String,
+}
+
+//A run of glyphs on one baseline without a wide gap
+#[derive(Debug, Clone)]
+struct Segment {
+    x: f64,
+    y: f64,
+    end: f64,
+    size: f64,
+    text: String,
+}
+
+//Collects positioned glyphs from pdf-extract and writes each page in reading order when it ends
+//(the layout counterpart of pdf-extract's PlainTextOutput)
+pub struct LayoutOutput<W: ConvertToFmt> {
+    writer: W::Writer,
+    page_height: f64,
+    glyphs: Vec<Glyph>,
+}
+
+impl<W: ConvertToFmt> LayoutOutput<W> {
+    pub fn new(writer: W) -> LayoutOutput<W> {
+        LayoutOutput { writer: writer.convert(), page_height: 0.0, glyphs: Vec::new() }
+    }
+}
+
+impl<W: ConvertToFmt> OutputDev for LayoutOutput<W> {
+    fn begin_page(&mut self, _page_num: u32, media_box: &MediaBox, _art_box: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.page_height = media_box.ury - media_box.lly;
+        self.glyphs.clear();
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        let glyphs: Vec<Glyph> = std::mem::take(&mut self.glyphs);
+        write!(self.writer, "{}", reading_order(glyphs))?;
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, _spacing: f64, font_size: f64, char: &str) -> Result<(), OutputError> {
+        //font size in device space: side of the square with the area of the transformed (size, size) vector
+        let size_x: f64 = font_size * (trm.m11 + trm.m21);
+        let size_y: f64 = font_size * (trm.m12 + trm.m22);
+        let size: f64 = (size_x * size_y).abs().sqrt();
+        if char.trim().is_empty() || size == 0.0 {
+            return Ok(());
+        }
+        let x: f64 = trm.m31;
+        self.glyphs.push(Glyph { x, y: self.page_height - trm.m32, end: x + width * size, size, text: char.to_string() });
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+//Group glyphs into baselines, then split each baseline at wide gaps
+fn segments(mut glyphs: Vec<Glyph>) -> Vec<Segment> {
+    glyphs.sort_by(|a, b| a.y.total_cmp(&b.y));
+    let mut lines: Vec<Vec<Glyph>> = Vec::new();
+    for glyph in glyphs {
+        match lines.last_mut() {
+            Some(line) if (glyph.y - line[0].y).abs() <= line[0].size.max(glyph.size) * BASELINE_TOLERANCE => line.push(glyph),
+            _ => lines.push(vec![glyph]),
+        }
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for mut line in lines {
+        line.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let mut current: Option<Segment> = None;
+        for glyph in line {
+            match current.as_mut() {
+                Some(segment) if glyph.x - segment.end <= segment.size.max(glyph.size) * SEGMENT_GAP => {
+                    if glyph.x - segment.end > segment.size.max(glyph.size) * WORD_GAP {
+                        segment.text.push(' ');
+                    }
+                    segment.text.push_str(&glyph.text);
+                    segment.end = segment.end.max(glyph.end);
+                    segment.size = segment.size.max(glyph.size);
+                }
+                _ => {
+                    segments.extend(current.take());
+                    current = Some(Segment { x: glyph.x, y: glyph.y, end: glyph.end, size: glyph.size, text: glyph.text });
+                }
+            }
+        }
+        segments.extend(current);
+    }
+    segments
+}
+
+//x positions of the gutters between columns: gaps no segment crosses with enough segments on both sides.
+//Full-width titles and footers would close every gap, so a gap may be crossed by up to half as many
+//segments as the smaller column has. Ragged line ends next to a gutter pass the same test, so of
+//neighbouring candidates only the widest gap is kept
+fn gutters(segments: &[Segment]) -> Vec<f64> {
+    let mut edges: Vec<f64> = segments.iter().flat_map(|s| [s.x, s.end]).collect();
+    edges.sort_by(|a, b| a.total_cmp(b));
+    edges.dedup();
+
+    let mut gutters: Vec<f64> = Vec::new();
+    let mut widest: Option<(f64, f64)> = None;
+    for pair in edges.windows(2) {
+        let (left, right) = (pair[0], pair[1]);
+        let middle: f64 = (left + right) / 2.0;
+        let crossing: usize = segments.iter().filter(|s| s.x < middle && s.end > middle).count();
+        let before: usize = segments.iter().filter(|s| s.end <= middle).count();
+        let after: usize = segments.iter().filter(|s| s.x >= middle).count();
+        let candidate: bool = right - left >= MIN_GUTTER_WIDTH
+            && before >= MIN_COLUMN_LINES
+            && after >= MIN_COLUMN_LINES
+            && crossing <= before.min(after) / 2;
+
+        if candidate {
+            if widest.is_none_or(|(width, _)| right - left > width) {
+                widest = Some((right - left, middle));
+            }
+        } else if let Some((_, gutter)) = widest.take() {
+            gutters.push(gutter);
+        }
+    }
+    gutters.extend(widest.map(|(_, gutter)| gutter));
+    gutters
+}
+
+//Append the segments of one column, top to bottom, with blank lines between paragraphs
+fn push_column(text: &mut String, column: &mut Vec<&Segment>) {
+    column.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let mut previous: Option<&Segment> = None;
+    for segment in column.drain(..) {
+        if !text.is_empty() {
+            let paragraph: bool = previous.is_some_and(|p| segment.y - p.y > p.size * PARAGRAPH_GAP);
+            text.push_str(if paragraph { "\n\n" } else { "\n" });
+        }
+        text.push_str(&segment.text);
+        previous = Some(segment);
+    }
+}
+
+//Page text in reading order. Segments that cross a gutter (titles, figure captions, footers)
+//split the page into bands; inside a band the columns are read one after the other
+fn reading_order(glyphs: Vec<Glyph>) -> String {
+    let mut segments: Vec<Segment> = segments(glyphs);
+    let gutters: Vec<f64> = gutters(&segments);
+    segments.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let mut text: String = String::new();
+    let mut columns: Vec<Vec<&Segment>> = vec![Vec::new(); gutters.len() + 1];
+    for segment in &segments {
+        let spanning: bool = gutters.iter().any(|&g| segment.x < g && segment.end > g);
+        if spanning {
+            for column in &mut columns {
+                push_column(&mut text, column);
+            }
+            push_column(&mut text, &mut vec![segment]);
+        } else {
+            let column: usize = gutters.iter().filter(|&&g| segment.x >= g).count();
+            columns[column].push(segment);
+        }
+    }
+    for column in &mut columns {
+        push_column(&mut text, column);
+    }
+    text
+}
@@ -1,5 +1,5 @@
 #![allow(unused_imports)]
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use axum::{
     extract::{Multipart, Extension},
     response::{Html, IntoResponse},
@@ -8,7 +8,7 @@ use axum::{
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use rust_bert::pipelines::{keywords_extraction, ner::NERModel};
+use std::path::PathBuf;
 use std::net::SocketAddr; //SocketAddr: Represents a socket address (IP + port)
 use tower_http::services::ServeDir; //ServeDir: Lets you serve static files (HTML, CSS, JS)
 use serde::Serialize;
@@ -19,6 +19,25 @@ mod utils;
 mod analyze;
 mod export;
 mod pdf;
+mod abstractive;
+mod ner;
+mod models;
+mod keywords;
+mod corpus;
+mod lang;
+mod segment;
+mod clean;
+mod normalize;
+mod input;
+mod layout;
+mod blocks;
+mod report;
+mod output;
+mod batch;
+mod watch;
+mod cache;
+mod glossary;
+mod config;
 
 /*customize command-line help and metadata for CLI tool -> long-about is for detailed description
 run with --help/-h/--version, this will show the below info*/
@@ -26,6 +45,9 @@ run with --help/-h/--version, this will show the below info*/
 #[derive(Parser)]
 #[command(author, version, about = "Analyze lecture PDFs to extract keywords, summaries, and suggest resources", long_about = None)]
 struct Cli {
+    //--format and --output, valid on every subcommand
+    #[command(flatten)]
+    output: output::OutputArgs,
     #[command(subcommand)]
     command: Commands,
 }
@@ -38,35 +60,240 @@ enum Commands {
         export:String,
         #[arg(long, default_value_t = 5)]
         summary_sentences: usize,
+        //Sentences per outline section (only when the PDF has bookmarks)
+        #[arg(long, default_value_t = 2)]
+        section_sentences: usize,
+        //keyword (rank by keyword hits), textrank (sentence similarity graph) or abstractive (BART/T5 model)
+        #[arg(long, value_enum, default_value_t = analyze::SummaryMethod::Keyword)]
+        summarizer: analyze::SummaryMethod,
+        //Local directory with the abstractive model weights (rust_model.ot, config.json, vocab files); downloads BART-CNN if omitted
+        #[arg(long)]
+        summarization_model: Option<PathBuf>,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
     },
     Keywords {
         input:String,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
+    },
+    //Definitions of the lecture's keywords, mined from its own sentences; keywords it never defines are flagged
+    Glossary {
+        input:String,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
     },
     Summary {
         input:String,
         #[arg(long, default_value_t = 5)]
         summary_sentences: usize,
+        //Sentences per outline section (only when the PDF has bookmarks)
+        #[arg(long, default_value_t = 2)]
+        section_sentences: usize,
+        #[arg(long, value_enum, default_value_t = analyze::SummaryMethod::Keyword)]
+        summarizer: analyze::SummaryMethod,
+        #[arg(long)]
+        summarization_model: Option<PathBuf>,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
     },
     Entities {
         input:String,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
     },
     Resources {
         input:String,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
+    },
+    //Analyze many lectures (directories, glob patterns or files) with the models loaded once;
+    //one report per lecture in --format plus index.json go to --out-dir (default <output dir>/batch)
+    Batch {
+        #[arg(required = true)]
+        inputs:Vec<String>,
+        //Directory for the reports and index.json
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+        //Lectures in flight at once: extraction runs in parallel, the model stages take turns
+        #[arg(long, default_value_t = 4)]
+        jobs: usize,
+        //Also run named entity recognition (NER model) on every lecture
+        #[arg(long)]
+        entities: bool,
+        //Also suggest Wikipedia resources (network requests per keyword)
+        #[arg(long)]
+        resources: bool,
+        #[arg(long, default_value_t = 5)]
+        summary_sentences: usize,
+        #[arg(long, default_value_t = 2)]
+        section_sentences: usize,
+        #[arg(long, value_enum, default_value_t = analyze::SummaryMethod::Keyword)]
+        summarizer: analyze::SummaryMethod,
+        #[arg(long)]
+        summarization_model: Option<PathBuf>,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        page_args: pdf::PageArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
+    },
+    //Re-analyze the lectures in a folder when they are added or saved: JSON reports and summary PDFs
+    //go to --out-dir (default the output directory)
+    Watch {
+        dir:String,
+        //Directory for the JSON reports and summary PDFs
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+        //Wait this long after a file's last change before analyzing it (editors save in bursts)
+        #[arg(long, default_value_t = 2000)]
+        debounce_ms: u64,
+        //Also suggest Wikipedia resources (network requests per keyword on every update)
+        #[arg(long)]
+        resources: bool,
+        #[arg(long, default_value_t = 5)]
+        summary_sentences: usize,
+        #[arg(long, default_value_t = 2)]
+        section_sentences: usize,
+        #[arg(long, value_enum, default_value_t = analyze::SummaryMethod::Keyword)]
+        summarizer: analyze::SummaryMethod,
+        #[arg(long)]
+        summarization_model: Option<PathBuf>,
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
+    },
+    //Index a directory of lectures for TF-IDF keywords (--method tfidf)
+    //(the index goes to --index, default <output dir>/corpus_index.json)
+    Index {
+        dir:String,
+        //Where the corpus index is written
+        #[arg(long)]
+        index: Option<PathBuf>,
+        #[command(flatten)]
+        language: lang::LanguageArgs,
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
     },
     //Start the web server for uploading and summarizing PDFs
     Serve {
-        #[arg(long, default_value_t = 8080)]
-        port: u16,
+        //Address and port to listen on (defaults from the config file, 127.0.0.1:8080)
+        #[arg(long)]
+        bind: Option<std::net::IpAddr>,
+        #[arg(long)]
+        port: Option<u16>,
+        //Local directory for abstractive summarization weights (loaded on first use)
+        #[arg(long)]
+        summarization_model: Option<PathBuf>,
+        //Keyword extractor used for every upload
+        #[command(flatten)]
+        keyword_args: keywords::KeywordArgs,
+        //Extraction timeout and header/footer cleaning for uploads
+        #[command(flatten)]
+        extract_args: pdf::ExtractArgs,
+        //Repeated uploads of the same handout are answered from the result cache
+        #[command(flatten)]
+        cache_args: cache::CacheArgs,
+    },
+    //Inspect or empty the result cache (<output dir>/cache)
+    Cache {
+        #[command(subcommand)]
+        action: cache::CacheAction,
+    },
+    //Inspect the configuration (config files and LECTURE_ANALYZER_* variables)
+    Config {
+        #[command(subcommand)]
+        action: config::ConfigAction,
     },
     //add more subcommand if any 
 }
 
-//For API response
+//Body of a failed API request (shown in place of the summary)
+#[derive(Serialize)]
+struct ErrorResponse {
+    error:String,
+}
+
+type ApiError = (axum::http::StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: axum::http::StatusCode, message: String) -> ApiError {
+    (status, Json(ErrorResponse { error: message }))
+}
+
+//Sentences in the web UI summary, and per section
+const API_SUMMARY_SENTENCES: usize = 5;
+const API_SECTION_SENTENCES: usize = 2;
+
+//Options shared by the CLI subcommands: nothing but extraction, each subcommand turns on what it prints
+fn cli_options(extract_args:pdf::ExtractArgs, page_args:pdf::PageArgs, stopwords:lang::StopwordConfig, cache:Option<cache::ResultCache>) -> report::AnalysisOptions {
+    report::AnalysisOptions {
+        extract_args,
+        page_args,
+        stopwords,
+        summary_sentences: 0,
+        section_sentences: 0,
+        summarizer: analyze::SummaryMethod::default(),
+        glossary: false,
+        resources: false,
+        cache,
+    }
+}
+
+//Run the pipeline on a CLI input and write the subcommand's part of the report in the --format asked for
+async fn run_cli(input:&str, options:&report::AnalysisOptions, analyzers:&report::Analyzers, view:output::View, output_args:&output::OutputArgs) -> Result<report::AnalysisReport, Box<dyn std::error::Error + Send + Sync>> {
+    let report: report::AnalysisReport = report::analyze_lecture(input, input, options, analyzers).await?;
+    let show_removed: bool = options.extract_args.clean.show_removed && !options.extract_args.clean.no_clean;
+    let rendered: String = output::render(&report, view, output_args.format, show_removed)?;
+    output::write(&rendered, output_args.output.as_deref())?;
+    Ok(report)
+}
+
+//Printed by index (the index itself is written to --output)
 #[derive(Serialize)]
-struct SummaryResponse {
-    summary:Vec<String>,
-    keywords:Vec<String>,
-    resources:Vec<String>,
+struct IndexSummary {
+    documents:usize,
+    words:usize,
+    index:PathBuf,
 }
 
 /*Async functions that process web requests.
@@ -74,61 +301,110 @@ struct SummaryResponse {
 */
 
 //Handles the logic when a user uploads a PDF (request handler)
-async fn summarize_api(Extension(ner_model):Extension<Arc<Mutex<NERModel>>>,mut multipart: Multipart) -> impl IntoResponse {
-    use axum::extract::multipart::Field;
-    while let Some(field) = multipart.next_field().await.unwrap() {
-        if field.name() == Some("file") {
-            let data = field.bytes().await.unwrap();
-            //Save PDF temporarily (or process directly if supported)
-            let temp_path: &'static str = "temp_upload.pdf";
-            std::fs::write(temp_path,&data).unwrap();
-
-            //2. Use your exisitng pipeline
-            let lecture_text:String = match pdf::extract_text(temp_path) {
-                Ok(text) => text,
-                Err(_e) => {
-                return Json(SummaryResponse{
-                    summary: vec!["Faild to extract text".into()],
-                    keywords: vec![],
-                    resources: vec![],
-                })
-            }
+async fn summarize_api(
+    Extension(analyzers):Extension<report::Analyzers>,
+    Extension(stopword_config):Extension<Arc<lang::StopwordConfig>>,
+    Extension(extract_args):Extension<Arc<pdf::ExtractArgs>>,
+    Extension(result_cache):Extension<Option<cache::ResultCache>>,
+    mut multipart: Multipart,
+) -> Result<Json<report::AnalysisReport>, ApiError> {
+    use axum::http::StatusCode;
+    //Collect the form first: options may arrive before or after the file
+    let mut file_data = None;
+    let mut file_name: String = String::new();
+    let mut summarizer: analyze::SummaryMethod = analyze::SummaryMethod::default();
+    let mut language: lang::Lang = stopword_config.lang;
+    let mut page_args: pdf::PageArgs = pdf::PageArgs::default();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return Err(api_error(StatusCode::BAD_REQUEST, format!("Invalid upload: {}", e))),
         };
-        //lock model and pass mutable ref to analyze function
-            let mut model = ner_model.lock().await;
-            let keywords: Vec<String> = analyze::extract_keywords_ner(&mut model, &lecture_text);
-            let summary: Vec<String> = analyze::extract_summary(&lecture_text,5,&keywords);
-            let resources: Vec<String> = match utils::suggest_resources(&keywords).await {
-                Ok(r) => r,
-                Err(_) => vec![],
-            };
-
-            //cleanup
-            let _  = std::fs::remove_file(temp_path); //"let _" ignore the result
-
-            return Json(SummaryResponse { 
-                summary,
-                keywords,
-                resources,
-            });
+        match field.name() {
+            Some("file") => {
+                //the extension tells the format apart when the content doesn't (.md vs .txt)
+                file_name = field.file_name().unwrap_or_default().to_string();
+                match field.bytes().await {
+                    Ok(bytes) => file_data = Some(bytes),
+                    Err(e) => return Err(api_error(StatusCode::BAD_REQUEST, format!("Failed to read upload: {}", e))),
+                }
+            }
+            Some("summarizer") => {
+                let value: String = field.text().await.unwrap_or_default();
+                summarizer = analyze::SummaryMethod::from_str(&value, true).unwrap_or_default();
+            }
+            Some("lang") => {
+                let value: String = field.text().await.unwrap_or_default();
+                language = lang::Lang::from_str(&value, true).unwrap_or(language);
+            }
+            //"1-5,9" like --pages; empty means all pages
+            Some("pages") => {
+                let value: String = field.text().await.unwrap_or_default();
+                if !value.trim().is_empty() {
+                    match value.parse::<pdf::PageRanges>() {
+                        Ok(ranges) => page_args.pages = Some(ranges),
+                        Err(e) => return Err(api_error(StatusCode::BAD_REQUEST, format!("Invalid pages: {}", e))),
+                    }
+                }
+            }
+            Some("section") => {
+                let value: String = field.text().await.unwrap_or_default();
+                page_args.section = Some(value.trim().to_string()).filter(|s| !s.is_empty());
+            }
+            _ => {}
         }
     }
-    
+
     //If no file was found
-    Json(SummaryResponse { 
-        summary: vec!["No file uploaded".into()], 
-        keywords: vec![], 
-        resources: vec![] 
-    })
+    let Some(data) = file_data else {
+        return Err(api_error(StatusCode::BAD_REQUEST, "No file uploaded".into()));
+    };
+
+    //Save the upload to a temp file of its own (removed when dropped), so parallel uploads don't clash;
+    //it keeps the uploaded extension for format detection
+    let extension: String = std::path::Path::new(&file_name)
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let temp_file: tempfile::NamedTempFile = tempfile::Builder::new()
+        .suffix(&extension)
+        .tempfile()
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to store upload: {}", e)))?;
+    std::fs::write(temp_file.path(), &data)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to store upload: {}", e)))?;
+
+    //2. Use the same pipeline as the CLI; form language overrides the server default, extra stopwords always apply
+    let options: report::AnalysisOptions = report::AnalysisOptions {
+        extract_args: (*extract_args).clone(),
+        page_args,
+        stopwords: lang::StopwordConfig { lang: language, extra: stopword_config.extra.clone() },
+        summary_sentences: API_SUMMARY_SENTENCES,
+        section_sentences: API_SECTION_SENTENCES,
+        summarizer,
+        glossary: true,
+        resources: true,
+        cache: result_cache,
+    };
+    let temp_path: String = temp_file.path().to_string_lossy().to_string();
+    let report: report::AnalysisReport = report::analyze_lecture(&temp_path, &file_name, &options, &analyzers)
+        .await
+        .map_err(|e| api_error(StatusCode::UNPROCESSABLE_ENTITY, format!("Failed to analyze {}: {}", file_name, e)))?;
+    Ok(Json(report))
 }
 
 //Start the Axum web server and defines what to do for each route
-async fn run_server(port: u16) -> Result<()> {
+async fn run_server(bind: std::net::IpAddr, port: u16, summarization_model: Option<PathBuf>, keyword_args: keywords::KeywordArgs, extract_args: pdf::ExtractArgs, cache_args: cache::CacheArgs) -> Result<()> {
     
     let static_files: ServeDir = ServeDir::new("./static");
+    let result_cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), summarization_model.as_deref());
 
-    //Create NERModel ONCE, wrap with Arc<Mutex<>> for safe sharing across async tasks
-    let ner_model = Arc::new(Mutex::new(NERModel::new(Default::default())?));
+    //Create the keyword extractor (and NERModel if picked) ONCE, Analyzers wraps each in Arc<Mutex<>> for safe sharing across async tasks
+    let extractor: Box<dyn keywords::KeywordExtractor> = keywords::build_extractor(&keyword_args).map_err(|e| anyhow::anyhow!(e))?;
+    let stopword_config = Arc::new(keyword_args.language.config().map_err(|e| anyhow::anyhow!(e))?);
+    //Abstractive model is big, so it is only loaded when the first request asks for it
+    let summarizer_model = abstractive::LazySummarizer::new(summarization_model);
+    let analyzers: report::Analyzers = report::Analyzers::new(Some(extractor), Some(summarizer_model), None);
 
     //::<()> or : Router<()> when creating your Router if you are not using shared state.
     let app = Router::new()
@@ -150,10 +426,13 @@ async fn run_server(port: u16) -> Result<()> {
             )
         }),
     )
-    .layer(Extension(ner_model)); //Add shared state layer
+    .layer(Extension(analyzers)) //Add shared state layer
+    .layer(Extension(stopword_config))
+    .layer(Extension(Arc::new(extract_args)))
+    .layer(Extension(result_cache));
 
     //start service request with app 
-    let addr =  SocketAddr::from(([127,0,0,1], port));
+    let addr =  SocketAddr::from((bind, port));
     println!("Server running at http://{}", addr);
     
     //create a server that listens on the specified address and serves the app
@@ -170,63 +449,184 @@ async fn run_server(port: u16) -> Result<()> {
 
 #[tokio::main]
 async fn main() ->Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    //1. Parse CLI arguments{
+    //1. Parse CLI arguments first, so --help works whatever the config files say
     let cli = Cli::parse();
+    //2. Read the config file(s): they provide the defaults of the flags left out. `config show` is how a
+    //broken file gets diagnosed, so it shows what did load and which layer failed instead of stopping
+    let (settings, failed): (&config::Config, Vec<config::LayerError>) = match cli.command {
+        Commands::Config { .. } => config::load_partial(),
+        _ => (config::load()?, Vec::new()),
+    };
     match cli.command {
-        Commands::Serve { port } => {
-                run_server(port).await?; 
+        Commands::Serve { bind, port, summarization_model, keyword_args, extract_args, cache_args } => {
+                run_server(bind.unwrap_or(settings.server.bind), port.unwrap_or(settings.server.port), summarization_model, keyword_args, extract_args, cache_args).await?; 
         }
-        Commands::Analyze { input, export, summary_sentences } => {
-            let lecture_text:String = pdf::extract_text(&input)?;
-            //New up a model just for CLI mode (not the server)
-            let mut model = NERModel::new(Default::default())?;
-            let keywords:Vec<String>  = analyze::extract_keywords_ner(&mut model, &lecture_text);
-            let summary: Vec<String> = analyze::extract_summary(&lecture_text, summary_sentences,&keywords);
-            let resources: Vec<String> = utils::suggest_resources(&keywords).await?;
-            println!("Exporting resources, count: {}", resources.len());
-            for r in &resources {
-                println!("Resource: {}", r);
+        Commands::Config { action: config::ConfigAction::Show } => {
+            for layer in &failed {
+                eprintln!("Config layer {} failed to load and was left out: {}", layer.layer, layer.message);
+            }
+            match output::render_data(settings, cli.output.format)? {
+                Some(data) => output::write(&data, cli.output.output.as_deref())?,
+                None => {
+                    //multi-line parser messages stay inside the comment
+                    let failures: String = failed
+                        .iter()
+                        .map(|layer| format!("# failed: {}: {}\n", layer.layer, layer.message.replace('\n', "\n#   ")))
+                        .collect();
+                    let text: String = format!("# sources: {}\n{}{}", settings.sources.join(", "), failures, settings.to_toml()?);
+                    output::write(&text, cli.output.output.as_deref())?;
+                }
             }
-            export::export_summary_to_pdf(&export, &summary.join("\n"), &keywords, &resources)?;
-            println!("Analysis complete. Summary exported to {}", export);
         }
-        Commands::Keywords { input } => {
-            let lecture_text:String = pdf::extract_text(&input)?;
-            let mut model = NERModel::new(Default::default())?;
-            let keywords:Vec<String>  = analyze::extract_keywords_ner(&mut model,&lecture_text);
-            println!("Extracted Keywords:");
-            for keyword in keywords {
-                println!("- {}", keyword);
-            } 
+        Commands::Cache { action } => {
+            let stats: cache::CacheStats = match action {
+                cache::CacheAction::Clear => cache::clear()?,
+                cache::CacheAction::Stats => cache::stats()?,
+            };
+            let text: String = match output::render_data(&stats, cli.output.format)? {
+                Some(data) => data,
+                None => match action {
+                    cache::CacheAction::Clear => format!("Removed {} cached reports ({} bytes) from {}\n", stats.entries, stats.bytes, stats.directory.display()),
+                    cache::CacheAction::Stats => format!("{} cached reports ({} bytes) in {}\n", stats.entries, stats.bytes, stats.directory.display()),
+                },
+            };
+            output::write(&text, cli.output.output.as_deref())?;
         }
-        Commands::Summary { input, summary_sentences } => {
-            let lecture_text:String = pdf::extract_text(&input)?;
-            let mut model = NERModel::new(Default::default())?;
-            let keywords:Vec<String>  = analyze::extract_keywords_ner(&mut model,&lecture_text);
-            let summary: Vec<String> = analyze::extract_summary(&lecture_text, summary_sentences,&keywords);
-            println!("Extracted Summary:");
-            for sentence in summary {
-                println!("- {}", sentence);
-            } 
+        Commands::Analyze { input, export, summary_sentences, section_sentences, summarizer, summarization_model, keyword_args, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), summarization_model.as_deref());
+            //New up an extractor just for CLI mode (not the server)
+            let analyzers: report::Analyzers = report::Analyzers::new(
+                Some(keywords::build_extractor(&keyword_args)?),
+                Some(abstractive::LazySummarizer::new(summarization_model)),
+                None,
+            );
+            let options: report::AnalysisOptions = report::AnalysisOptions {
+                summary_sentences,
+                section_sentences,
+                summarizer,
+                glossary: true,
+                resources: true,
+                ..cli_options(extract_args, page_args, keyword_args.language.config()?, cache)
+            };
+            let report: report::AnalysisReport = run_cli(&input, &options, &analyzers, output::View::Analyze, &cli.output).await?;
+            export::export_summary_to_pdf(&settings.output.dir, &export, &report)?;
+            //status on stderr, stdout only carries the --format output
+            eprintln!("Analysis complete. Summary exported to {}", export);
+        }
+        Commands::Keywords { input, keyword_args, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), None);
+            let analyzers: report::Analyzers = report::Analyzers::new(Some(keywords::build_extractor(&keyword_args)?), None, None);
+            let options: report::AnalysisOptions = cli_options(extract_args, page_args, keyword_args.language.config()?, cache);
+            run_cli(&input, &options, &analyzers, output::View::Keywords, &cli.output).await?;
         }
-        Commands::Resources { input } => {
-            let lecture_text:String = pdf::extract_text(&input)?;
-            let mut model = NERModel::new(Default::default())?;
-            let keywords:Vec<String>  = analyze::extract_keywords_ner(&mut model,&lecture_text);
-            let resources: Vec<String> = utils::suggest_resources(&keywords).await?;
-            println!("Suggested Resources:");
-            for resource in resources {
-                println!("- {}", resource);
+        Commands::Glossary { input, keyword_args, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), None);
+            let analyzers: report::Analyzers = report::Analyzers::new(Some(keywords::build_extractor(&keyword_args)?), None, None);
+            let options: report::AnalysisOptions = report::AnalysisOptions {
+                glossary: true,
+                ..cli_options(extract_args, page_args, keyword_args.language.config()?, cache)
+            };
+            run_cli(&input, &options, &analyzers, output::View::Glossary, &cli.output).await?;
+        }
+        Commands::Summary { input, summary_sentences, section_sentences, summarizer, summarization_model, keyword_args, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), summarization_model.as_deref());
+            let analyzers: report::Analyzers = report::Analyzers::new(
+                Some(keywords::build_extractor(&keyword_args)?),
+                Some(abstractive::LazySummarizer::new(summarization_model)),
+                None,
+            );
+            let options: report::AnalysisOptions = report::AnalysisOptions {
+                summary_sentences,
+                section_sentences,
+                summarizer,
+                ..cli_options(extract_args, page_args, keyword_args.language.config()?, cache)
+            };
+            run_cli(&input, &options, &analyzers, output::View::Summary, &cli.output).await?;
+        }
+        Commands::Resources { input, keyword_args, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), None);
+            let analyzers: report::Analyzers = report::Analyzers::new(Some(keywords::build_extractor(&keyword_args)?), None, None);
+            let options: report::AnalysisOptions = report::AnalysisOptions {
+                resources: true,
+                ..cli_options(extract_args, page_args, keyword_args.language.config()?, cache)
+            };
+            run_cli(&input, &options, &analyzers, output::View::Resources, &cli.output).await?;
+        }
+        Commands::Batch { inputs, out_dir, jobs, entities, resources, summary_sentences, section_sentences, summarizer, summarization_model, keyword_args, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), summarization_model.as_deref());
+            let files: Vec<PathBuf> = batch::collect_inputs(&inputs)?;
+            if files.is_empty() {
+                return Err(format!("no lectures found in {}", inputs.join(", ")).into());
             }
+            let ner_model: Option<ner::NERModel> = if entities { Some(ner::load_model()?) } else { None };
+            let analyzers: report::Analyzers = report::Analyzers::new(
+                Some(keywords::build_extractor(&keyword_args)?),
+                Some(abstractive::LazySummarizer::new(summarization_model)),
+                ner_model,
+            );
+            let options: report::AnalysisOptions = report::AnalysisOptions {
+                summary_sentences,
+                section_sentences,
+                summarizer,
+                glossary: true,
+                resources,
+                ..cli_options(extract_args, page_args, keyword_args.language.config()?, cache)
+            };
+            let out_dir: PathBuf = out_dir.unwrap_or_else(|| settings.output.dir.join("batch"));
+            let index: batch::BatchIndex = batch::run_batch(files, &out_dir, cli.output.format, Arc::new(options), &analyzers, jobs).await?;
+            let text: String = match output::render_data(&index, cli.output.format)? {
+                Some(data) => data,
+                None => {
+                    let mut text: String = format!("Analyzed {} of {} lectures into {} (index.json lists the reports)\n", index.analyzed, index.lectures.len(), out_dir.display());
+                    if index.failed > 0 {
+                        text.push_str("Failed:\n");
+                        for lecture in index.lectures.iter().filter(|l| l.error.is_some()) {
+                            text.push_str(&format!("- {}: {}\n", lecture.file, lecture.error.as_deref().unwrap_or_default()));
+                        }
+                    }
+                    text
+                }
+            };
+            output::write(&text, cli.output.output.as_deref())?;
+            //non-zero exit status for scripts, after every other lecture was done
+            if index.failed > 0 {
+                return Err(format!("{} of {} lectures failed", index.failed, index.lectures.len()).into());
+            }
+        }
+        Commands::Watch { dir, out_dir, debounce_ms, resources, summary_sentences, section_sentences, summarizer, summarization_model, keyword_args, extract_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(Some(&keyword_args), summarization_model.as_deref());
+            let analyzers: report::Analyzers = report::Analyzers::new(
+                Some(keywords::build_extractor(&keyword_args)?),
+                Some(abstractive::LazySummarizer::new(summarization_model)),
+                None,
+            );
+            let options: report::AnalysisOptions = report::AnalysisOptions {
+                summary_sentences,
+                section_sentences,
+                summarizer,
+                glossary: true,
+                resources,
+                ..cli_options(extract_args, pdf::PageArgs::default(), keyword_args.language.config()?, cache)
+            };
+            let out_dir: PathBuf = out_dir.unwrap_or_else(|| settings.output.dir.clone());
+            watch::watch(std::path::Path::new(&dir), &out_dir, std::time::Duration::from_millis(debounce_ms), &options, &analyzers).await?;
+        }
+        Commands::Index { dir, index, language, extract_args } => {
+            let path: PathBuf = index.unwrap_or_else(|| settings.corpus_index());
+            let index: corpus::CorpusIndex = corpus::build_index(std::path::Path::new(&dir), &language.config()?, &extract_args)?;
+            index.save(&path)?;
+            let summary: IndexSummary = IndexSummary { documents: index.documents, words: index.document_frequency.len(), index: path };
+            let text: String = match output::render_data(&summary, cli.output.format)? {
+                Some(data) => data,
+                None => format!("Indexed {} lectures ({} distinct words) into {}\n", summary.documents, summary.words, summary.index.display()),
+            };
+            output::write(&text, cli.output.output.as_deref())?;
         }
-        Commands::Entities { input } => {
-            let lecture_text:String = pdf::extract_text(&input)?;
-            let mut model = NERModel::new(Default::default())?;
-            let entities: Vec<rust_bert::pipelines::ner::Entity> = analyze::extract_entities_ner(&mut model, &lecture_text);
-            println!("Extracted Entities:");
-            for entity in entities {
-                println!("Word: {}, Label: {}, Score: {}", entity.word, entity.label, entity.score);
-            } 
+        Commands::Entities { input, extract_args, page_args, cache_args } => {
+            let cache: Option<cache::ResultCache> = cache_args.cache(None, None);
+            let analyzers: report::Analyzers = report::Analyzers::new(None, None, Some(ner::load_model()?));
+            let options: report::AnalysisOptions = cli_options(extract_args, page_args, lang::StopwordConfig::default(), cache);
+            run_cli(&input, &options, &analyzers, output::View::Entities, &cli.output).await?;
         }
     }
 
@@ -0,0 +1,18 @@
+//The rust-bert models (NER keywords and entities, abstractive summaries) need libtorch at build time, so they
+//are behind the `models` cargo feature. Without it the model types are stand-ins that cannot be constructed:
+//loading one fails with ModelError, and frequency, RAKE and TF-IDF keywords and the other summarizers work as usual
+#[cfg(feature = "models")]
+pub use rust_bert::RustBertError as ModelError;
+
+#[cfg(not(feature = "models"))]
+#[derive(thiserror::Error)]
+#[error("{0} needs a build with the `models` feature (cargo build --features models, requires libtorch)")]
+pub struct ModelError(pub &'static str);
+
+//main prints returned errors with Debug: show the message, not the struct
+#[cfg(not(feature = "models"))]
+impl std::fmt::Debug for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
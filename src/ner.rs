@@ -0,0 +1,458 @@
+#[cfg(feature = "models")]
+use rust_bert::pipelines::ner::Entity;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::analyze::PageEntity;
+use crate::models::ModelError;
+use crate::pdf::Document;
+#[cfg(any(feature = "models", test))]
+use crate::pdf::Page;
+#[cfg(any(feature = "models", test))]
+use crate::segment;
+
+#[cfg(feature = "models")]
+pub use rust_bert::pipelines::ner::NERModel;
+
+//Stand-in without the `models` feature: it has no values, so code holding one can never run
+#[cfg(not(feature = "models"))]
+pub enum NERModel {}
+
+//Model behind NER keywords and entities (rust-bert's default NER pipeline), shown in reports
+pub const MODEL_VERSION: &str = "rust-bert 0.23 (bert-large-cased CoNLL-03)";
+
+//BERT reads at most 512 word pieces; ~1000 characters stays well below that
+#[cfg(any(feature = "models", test))]
+const MAX_CHUNK_CHARS: usize = 1000;
+//Text shared by neighbouring chunks so entities on a chunk border are still seen whole
+#[cfg(any(feature = "models", test))]
+const OVERLAP_CHARS: usize = 200;
+
+//Piece of one page sent to the model, start is its char offset in the page text
+#[cfg(any(feature = "models", test))]
+pub struct TextChunk {
+    pub page: u32,
+    pub start: usize,
+    pub text: String,
+}
+
+//Cut a sentence longer than a chunk into chunk-sized pieces, each starting overlap_chars
+//before the end of the previous one
+#[cfg(any(feature = "models", test))]
+fn sentence_pieces(start: usize, end: usize, max_chars: usize, overlap_chars: usize) -> Vec<(usize, usize)> {
+    let step: usize = max_chars.saturating_sub(overlap_chars).max(1);
+    let mut pieces: Vec<(usize, usize)> = Vec::new();
+    let mut a: usize = start;
+    loop {
+        let b: usize = (a + max_chars).min(end);
+        pieces.push((a, b));
+        if b >= end {
+            break;
+        }
+        a += step;
+    }
+    pieces
+}
+
+//Pack whole sentences into chunks of at most max_chars, repeating trailing sentences
+//(up to overlap_chars) at the start of the next chunk
+#[cfg(any(feature = "models", test))]
+pub fn chunk_page(page: &Page, max_chars: usize, overlap_chars: usize) -> Vec<TextChunk> {
+    let chars: Vec<char> = page.text.chars().collect();
+
+    let spans: Vec<(usize, usize)> = segment::segment(&page.text)
+        .into_iter()
+        .flat_map(|sentence| sentence_pieces(sentence.start, sentence.end, max_chars, overlap_chars))
+        .collect();
+
+    let mut chunks: Vec<TextChunk> = Vec::new();
+    let mut i: usize = 0;
+    while i < spans.len() {
+        let chunk_start: usize = spans[i].0;
+        let mut j: usize = i;
+        while j < spans.len() && spans[j].1 - chunk_start <= max_chars {
+            j += 1;
+        }
+        let chunk_end: usize = spans[j - 1].1;
+        let text: String = chars[chunk_start..chunk_end].iter().collect();
+        if !text.trim().is_empty() {
+            chunks.push(TextChunk { page: page.number, start: chunk_start, text });
+        }
+        if j >= spans.len() {
+            break;
+        }
+
+        //step back over sentences that fit in the overlap, but always move forward
+        let mut k: usize = j;
+        while k > i + 1 && chunk_end - spans[k - 1].0 <= overlap_chars {
+            k -= 1;
+        }
+        i = k;
+    }
+    chunks
+}
+
+#[cfg(feature = "models")]
+pub fn chunk_document(document: &Document) -> Vec<TextChunk> {
+    document
+        .pages
+        .iter()
+        .flat_map(|page| chunk_page(page, MAX_CHUNK_CHARS, OVERLAP_CHARS))
+        .collect()
+}
+
+//rust-bert's default NER pipeline (downloads the weights on first use)
+#[cfg(feature = "models")]
+pub fn load_model() -> Result<NERModel, ModelError> {
+    NERModel::new(Default::default())
+}
+
+#[cfg(not(feature = "models"))]
+pub fn load_model() -> Result<NERModel, ModelError> {
+    Err(ModelError("NER"))
+}
+
+//Map chunk-relative offsets (start/end of each prediction) back to the page and drop duplicates
+//from overlapping chunks: one entity per page and span, the most confident prediction wins even
+//when the chunks disagree on the label (B-PER where a chunk starts mid-name, I-PER in the other)
+#[cfg(any(feature = "models", test))]
+pub fn merge_entities(chunks: &[TextChunk], predictions: Vec<Vec<PageEntity>>) -> Vec<PageEntity> {
+    let mut merged: HashMap<(u32, usize, usize), PageEntity> = HashMap::new();
+    for (chunk, entities) in chunks.iter().zip(predictions) {
+        for entity in entities {
+            let start: usize = chunk.start + entity.start;
+            let end: usize = chunk.start + entity.end;
+            let key = (chunk.page, start, end);
+            let located = PageEntity {
+                page: chunk.page,
+                start,
+                end,
+                ..entity
+            };
+            match merged.get(&key) {
+                Some(existing) if existing.score >= located.score => {}
+                _ => {
+                    merged.insert(key, located);
+                }
+            }
+        }
+    }
+
+    let mut entities: Vec<PageEntity> = merged.into_values().collect();
+    entities.sort_by_key(|e| (e.page, e.start, e.end));
+    entities
+}
+
+//Run NER over the whole lecture: every chunk goes into a single batched predict call
+#[cfg(feature = "models")]
+pub fn predict_chunked(model: &NERModel, document: &Document) -> Vec<PageEntity> {
+    let chunks: Vec<TextChunk> = chunk_document(document);
+    if chunks.is_empty() {
+        return Vec::new();
+    }
+    let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+    let predictions: Vec<Vec<PageEntity>> = model
+        .predict(&texts)
+        .into_iter()
+        .map(|entities: Vec<Entity>| {
+            entities
+                .into_iter()
+                .map(|entity| PageEntity {
+                    word: entity.word,
+                    label: entity.label,
+                    score: entity.score,
+                    page: 0,
+                    start: entity.offset.begin as usize,
+                    end: entity.offset.end as usize,
+                })
+                .collect()
+        })
+        .collect();
+    merge_entities(&chunks, predictions)
+}
+
+#[cfg(not(feature = "models"))]
+pub fn predict_chunked(model: &NERModel, _document: &Document) -> Vec<PageEntity> {
+    match *model {}
+}
+
+//Where one mention of an aggregated entity sits (char offsets in the page text)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityMention {
+    pub page: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+//Whole entity ("Alan Turing", not "Alan" + "Turing" or "##tron") with all of its mentions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatedEntity {
+    pub text: String,
+    //entity type without the B-/I- prefix, e.g. PER
+    pub label: String,
+    //mean token score over every mention
+    pub confidence: f64,
+    pub count: usize,
+    pub mentions: Vec<EntityMention>,
+}
+
+//"B-PER" -> ("B", "PER"), "PER" -> ("", "PER")
+fn split_label(label: &str) -> (&str, &str) {
+    match label.split_once('-') {
+        Some((prefix, kind)) if prefix == "B" || prefix == "I" => (prefix, kind),
+        _ => ("", label),
+    }
+}
+
+//One entity span built from consecutive tokens
+struct Span {
+    label: String,
+    page: u32,
+    start: usize,
+    end: usize,
+    scores: Vec<f64>,
+    words: Vec<String>,
+}
+
+//Join word pieces and I- continuations into spans, in page/offset order
+fn group_spans(entities: &[PageEntity], page_chars: &HashMap<u32, Vec<char>>) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    for entity in entities {
+        let (prefix, kind) = split_label(&entity.label);
+        let is_piece: bool = entity.word.starts_with("##");
+        let word: String = entity.word.trim_start_matches("##").to_string();
+
+        let continues: bool = match spans.last() {
+            Some(span) if span.page == entity.page && span.label == kind && span.end <= entity.start => {
+                //only whitespace (at most one char) between the two tokens
+                let gap: String = page_chars
+                    .get(&entity.page)
+                    .map(|chars| chars[span.end.min(chars.len())..entity.start.min(chars.len())].iter().collect())
+                    .unwrap_or_default();
+                let adjacent: bool = gap.chars().count() <= 1 && gap.trim().is_empty();
+                adjacent && (is_piece || prefix != "B")
+            }
+            _ => false,
+        };
+
+        if continues {
+            let span: &mut Span = spans.last_mut().unwrap();
+            span.end = entity.end;
+            span.scores.push(entity.score);
+            if is_piece {
+                if let Some(last) = span.words.last_mut() {
+                    last.push_str(&word);
+                }
+            } else {
+                span.words.push(word);
+            }
+        } else {
+            spans.push(Span {
+                label: kind.to_string(),
+                page: entity.page,
+                start: entity.start,
+                end: entity.end,
+                scores: vec![entity.score],
+                words: vec![word],
+            });
+        }
+    }
+    spans
+}
+
+//Group token-level entities into whole entities and count how often each one is mentioned
+pub fn aggregate_entities(document: &Document, entities: &[PageEntity]) -> Vec<AggregatedEntity> {
+    let page_chars: HashMap<u32, Vec<char>> = document
+        .pages
+        .iter()
+        .map(|p| (p.number, p.text.chars().collect()))
+        .collect();
+
+    let mut aggregated: Vec<AggregatedEntity> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut score_sums: Vec<f64> = Vec::new();
+    let mut score_counts: Vec<usize> = Vec::new();
+
+    for span in group_spans(entities, &page_chars) {
+        //Prefer the original text (keeps its spacing/punctuation), fall back to the joined tokens
+        let text: String = match page_chars.get(&span.page) {
+            Some(chars) if span.end <= chars.len() && span.start < span.end => {
+                chars[span.start..span.end].iter().collect::<String>().split_whitespace().collect::<Vec<&str>>().join(" ")
+            }
+            _ => span.words.join(" "),
+        };
+        if text.is_empty() {
+            continue;
+        }
+
+        let key = (text.to_lowercase(), span.label.clone());
+        let i: usize = *index.entry(key).or_insert_with(|| {
+            aggregated.push(AggregatedEntity {
+                text: text.clone(),
+                label: span.label.clone(),
+                confidence: 0.0,
+                count: 0,
+                mentions: Vec::new(),
+            });
+            score_sums.push(0.0);
+            score_counts.push(0);
+            aggregated.len() - 1
+        });
+        let entity: &mut AggregatedEntity = &mut aggregated[i];
+        entity.count += 1;
+        entity.mentions.push(EntityMention { page: span.page, start: span.start, end: span.end });
+        score_sums[i] += span.scores.iter().sum::<f64>();
+        score_counts[i] += span.scores.len();
+    }
+
+    for (i, entity) in aggregated.iter_mut().enumerate() {
+        entity.confidence = score_sums[i] / score_counts[i] as f64;
+    }
+    //Most mentioned first, then most confident
+    aggregated.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    aggregated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(text: &str) -> Page {
+        Page { number: 1, text: text.to_string() }
+    }
+
+    fn entity(word: &str, label: &str, score: f64, start: usize, end: usize) -> PageEntity {
+        PageEntity { word: word.to_string(), label: label.to_string(), score, page: 0, start, end }
+    }
+
+    //40 sentences of 30 chars (with the space after them)
+    fn long_page() -> Page {
+        page(&(0..40).map(|i| format!("Sentence number {:02} is here.", i)).collect::<Vec<String>>().join(" "))
+    }
+
+    #[test]
+    fn splits_a_long_page_at_sentence_borders() {
+        let page: Page = long_page();
+        let chunks: Vec<TextChunk> = chunk_page(&page, MAX_CHUNK_CHARS, OVERLAP_CHARS);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= MAX_CHUNK_CHARS);
+            assert!(chunk.text.starts_with("Sentence number"));
+            assert!(chunk.text.ends_with("is here."));
+        }
+        assert!(chunks.last().unwrap().text.ends_with("39 is here."));
+    }
+
+    #[test]
+    fn carries_the_overlap_into_the_next_chunk() {
+        let chunks: Vec<TextChunk> = chunk_page(&long_page(), MAX_CHUNK_CHARS, OVERLAP_CHARS);
+        for pair in chunks.windows(2) {
+            let end: usize = pair[0].start + pair[0].text.chars().count();
+            assert!(pair[1].start < end);
+            assert!(end - pair[1].start <= OVERLAP_CHARS);
+            //the shared text is the same in both chunks
+            let shared: usize = end - pair[1].start;
+            let tail: String = pair[0].text.chars().skip(pair[0].text.chars().count() - shared).collect();
+            assert!(pair[1].text.starts_with(&tail));
+        }
+    }
+
+    #[test]
+    fn pieces_of_a_long_sentence_overlap() {
+        let text: String = "word ".repeat(500);
+        let chunks: Vec<TextChunk> = chunk_page(&page(text.trim_end()), MAX_CHUNK_CHARS, OVERLAP_CHARS);
+        assert!(chunks.len() > 2);
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[0].start + pair[0].text.chars().count() - pair[1].start, OVERLAP_CHARS);
+        }
+        assert_eq!(chunks.last().unwrap().start + chunks.last().unwrap().text.chars().count(), 2499);
+    }
+
+    #[test]
+    fn merges_duplicate_spans_from_overlapping_chunks() {
+        let chunks: Vec<TextChunk> = vec![
+            TextChunk { page: 3, start: 0, text: String::new() },
+            TextChunk { page: 3, start: 800, text: String::new() },
+        ];
+        let predictions: Vec<Vec<PageEntity>> = vec![
+            vec![entity("Alan", "I-PER", 0.9, 10, 14), entity("Turing", "I-PER", 0.7, 850, 856)],
+            vec![entity("Turing", "I-PER", 0.95, 50, 56), entity("Codd", "I-PER", 0.8, 300, 304)],
+        ];
+        let merged: Vec<PageEntity> = merge_entities(&chunks, predictions);
+        let spans: Vec<(&str, u32, usize, usize)> = merged.iter().map(|e| (e.word.as_str(), e.page, e.start, e.end)).collect();
+        assert_eq!(spans, vec![("Alan", 3, 10, 14), ("Turing", 3, 850, 856), ("Codd", 3, 1100, 1104)]);
+        assert_eq!(merged[1].score, 0.95);
+    }
+
+    #[test]
+    fn one_entity_per_span_even_when_labels_differ() {
+        let chunks: Vec<TextChunk> = vec![
+            TextChunk { page: 1, start: 0, text: String::new() },
+            TextChunk { page: 1, start: 800, text: String::new() },
+        ];
+        let predictions: Vec<Vec<PageEntity>> = vec![
+            vec![entity("Turing", "I-PER", 0.9, 850, 856)],
+            vec![entity("Turing", "B-PER", 0.6, 50, 56)],
+        ];
+        let merged: Vec<PageEntity> = merge_entities(&chunks, predictions);
+        assert_eq!(merged.len(), 1);
+        assert_eq!((merged[0].label.as_str(), merged[0].start), ("I-PER", 850));
+    }
+
+    const TEXT: &str = "Alan Turing met Grace Hopper. Alan Turing used ENIAC.";
+
+    //Token-level predictions for TEXT, located on page 1
+    fn tokens() -> Vec<PageEntity> {
+        [
+            ("Alan", "B-PER", 0.9, 0, 4),
+            ("Turing", "I-PER", 0.8, 5, 11),
+            ("Grace", "B-PER", 0.7, 16, 21),
+            ("Hopper", "B-PER", 0.6, 22, 28),
+            ("Alan", "I-PER", 1.0, 30, 34),
+            ("Turing", "I-PER", 0.6, 35, 41),
+            ("EN", "B-MISC", 0.5, 47, 49),
+            ("##IAC", "I-MISC", 0.7, 49, 52),
+        ]
+        .into_iter()
+        .map(|(word, label, score, start, end)| PageEntity { page: 1, ..entity(word, label, score, start, end) })
+        .collect()
+    }
+
+    fn document() -> Document {
+        Document { pages: vec![page(TEXT)], ..Default::default() }
+    }
+
+    #[test]
+    fn groups_pieces_and_continuations_into_spans() {
+        let page_chars: HashMap<u32, Vec<char>> = HashMap::from([(1, TEXT.chars().collect())]);
+        let spans: Vec<Span> = group_spans(&tokens(), &page_chars);
+        let found: Vec<(String, &str, usize, usize)> = spans.iter().map(|s| (s.words.join(" "), s.label.as_str(), s.start, s.end)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Alan Turing".to_string(), "PER", 0, 11),
+                ("Grace".to_string(), "PER", 16, 21),
+                ("Hopper".to_string(), "PER", 22, 28),
+                ("Alan Turing".to_string(), "PER", 30, 41),
+                ("ENIAC".to_string(), "MISC", 47, 52),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregates_mentions_and_mean_scores() {
+        let aggregated: Vec<AggregatedEntity> = aggregate_entities(&document(), &tokens());
+        let turing: &AggregatedEntity = &aggregated[0];
+        assert_eq!((turing.text.as_str(), turing.label.as_str(), turing.count), ("Alan Turing", "PER", 2));
+        assert!((turing.confidence - 0.825).abs() < 1e-9);
+        assert_eq!(turing.mentions.iter().map(|m| (m.start, m.end)).collect::<Vec<_>>(), vec![(0, 11), (30, 41)]);
+
+        let eniac: &AggregatedEntity = aggregated.iter().find(|e| e.text == "ENIAC").unwrap();
+        assert_eq!((eniac.label.as_str(), eniac.count), ("MISC", 1));
+        assert!((eniac.confidence - 0.6).abs() < 1e-9);
+        assert_eq!(aggregated.len(), 4);
+    }
+}
@@ -0,0 +1,88 @@
+//Text normalization between extraction and analysis: pdf-extract leaves ligature glyphs,
+//odd spaces, hyphenated line ends and hard breaks inside sentences in the page text.
+use crate::pdf::Document;
+
+//NFKC-like folding of the characters PDFs commonly contain, appended to `out`
+fn fold_char(c: char, out: &mut String) {
+    match c {
+        //ligatures
+        'ﬀ' => out.push_str("ff"),
+        'ﬁ' => out.push_str("fi"),
+        'ﬂ' => out.push_str("fl"),
+        'ﬃ' => out.push_str("ffi"),
+        'ﬄ' => out.push_str("ffl"),
+        'ﬅ' | 'ﬆ' => out.push_str("st"),
+        //no-break, thin, ideographic and other fixed-width spaces
+        '\u{a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' | '\t' => out.push(' '),
+        //zero-width characters and byte order marks
+        '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' => {}
+        '\u{2010}' | '\u{2011}' => out.push('-'),
+        '…' => out.push_str("..."),
+        //fullwidth forms (Ａ, ０, ！) are plain ASCII shifted up
+        '\u{ff01}'..='\u{ff5e}' => out.extend(char::from_u32(c as u32 - 0xfee0)),
+        //Symbol/Wingdings bullets exported into the private use area
+        '\u{f0a7}' | '\u{f0b7}' | '\u{f076}' | '\u{f0d8}' => out.push('•'),
+        _ => out.push(c),
+    }
+}
+
+//Unify Unicode: ligatures, spaces, fullwidth ASCII, soft hyphens and line endings
+pub fn fold_unicode(text: &str) -> String {
+    let chars: Vec<char> = text.replace("\r\n", "\n").replace('\r', "\n").chars().collect();
+    let mut folded: String = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        //a soft hyphen only shows when the word breaks at the line end: keep it there so
+        //the word is rejoined below, drop it everywhere else
+        if c == '\u{ad}' {
+            let at_line_end: bool = chars[i + 1..].iter().find(|c| **c != ' ').is_none_or(|c| *c == '\n');
+            if at_line_end {
+                folded.push('-');
+            }
+            continue;
+        }
+        fold_char(c, &mut folded);
+    }
+    folded
+}
+
+//Does the line end with a word broken by a hyphen ("informa-")?
+fn ends_with_broken_word(line: &str) -> bool {
+    let mut chars = line.chars().rev();
+    chars.next() == Some('-') && chars.next().is_some_and(|c| c.is_alphabetic())
+}
+
+//Fold Unicode, rejoin hyphenated words, merge wrapped lines into paragraphs and collapse whitespace.
+//A line continues the previous one when it starts lowercase or with '('; blank lines,
+//bullets and capitalized lines keep their breaks
+pub fn normalize_text(text: &str) -> String {
+    let folded: String = fold_unicode(text);
+    let mut normalized: String = String::new();
+    let mut previous_blank: bool = true;
+
+    for line in folded.lines() {
+        let line: String = line.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if line.is_empty() {
+            previous_blank = true;
+            continue;
+        }
+        let continues: bool = !previous_blank && line.starts_with(|c: char| c.is_lowercase() || c == '(');
+
+        if continues && ends_with_broken_word(&normalized) && line.starts_with(char::is_lowercase) {
+            normalized.pop();
+        } else if continues {
+            normalized.push(' ');
+        } else if !normalized.is_empty() {
+            normalized.push_str(if previous_blank { "\n\n" } else { "\n" });
+        }
+        normalized.push_str(&line);
+        previous_blank = false;
+    }
+    normalized
+}
+
+//Normalize every page in place
+pub fn normalize_document(document: &mut Document) {
+    for page in &mut document.pages {
+        page.text = normalize_text(&page.text);
+    }
+}
@@ -0,0 +1,329 @@
+//CLI output of an AnalysisReport: the human text each subcommand always printed, Markdown, or the
+//whole report as JSON/YAML (same schema as /api/summarize) for scripts; --output writes it to a file
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use crate::pdf::BlockKind;
+use crate::report::{format_page_range, format_pages, AnalysisReport};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Markdown,
+}
+
+//Global: accepted before or after the subcommand
+#[derive(Debug, Clone, Args)]
+pub struct OutputArgs {
+    //text (human readable), json or yaml (the full report, stable field names) or markdown
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    //Write the output to this file instead of stdout
+    #[arg(long, global = true)]
+    pub output: Option<PathBuf>,
+}
+
+//Which parts of the report a subcommand prints as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Analyze,
+    Keywords,
+    Glossary,
+    Summary,
+    Resources,
+    Entities,
+    //everything the report holds (batch reports)
+    Full,
+}
+
+//Render a value in a structured format; text and Markdown are up to the caller
+pub fn render_data<T: Serialize>(value: &T, format: OutputFormat) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    match format {
+        OutputFormat::Json => Ok(Some(serde_json::to_string_pretty(value)? + "\n")),
+        OutputFormat::Yaml => Ok(Some(serde_yaml::to_string(value)?)),
+        OutputFormat::Text | OutputFormat::Markdown => Ok(None),
+    }
+}
+
+//A subcommand's report in the chosen format; `show_removed` adds the stripped headers/footers to text and Markdown
+pub fn render(report: &AnalysisReport, view: View, format: OutputFormat, show_removed: bool) -> Result<String, Box<dyn Error + Send + Sync>> {
+    if let Some(data) = render_data(report, format)? {
+        return Ok(data);
+    }
+    let mut out: String = String::new();
+    match format {
+        OutputFormat::Markdown => {
+            markdown(&mut out, report);
+            if show_removed {
+                markdown_removed(&mut out, report);
+            }
+        }
+        _ => {
+            if show_removed {
+                text_removed(&mut out, report);
+            }
+            text(&mut out, report, view);
+        }
+    }
+    Ok(out)
+}
+
+//Print to stdout, or write the file (creating its directory) for --output
+pub fn write(rendered: &str, output: Option<&Path>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match output {
+        Some(path) => {
+            if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, rendered)?;
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+fn text_removed(out: &mut String, report: &AnalysisReport) {
+    out.push_str("Removed lines:\n");
+    for line in &report.removed {
+        let _ = writeln!(out, "- {:?} ({:?}, {})", line.text, line.reason, format_pages(&line.pages));
+    }
+}
+
+//Metadata and outline printed before the results
+fn text_details(out: &mut String, report: &AnalysisReport) {
+    for line in report.detail_lines() {
+        let _ = writeln!(out, "{}", line);
+    }
+    if !report.source.outline.is_empty() {
+        out.push_str("Outline:\n");
+        for line in report.outline_lines() {
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+}
+
+fn text_glossary(out: &mut String, report: &AnalysisReport) {
+    out.push_str("Glossary:\n");
+    for line in report.glossary_lines() {
+        let _ = writeln!(out, "- {}", line);
+    }
+    let undefined: Vec<&str> = report.undefined_terms();
+    if !undefined.is_empty() {
+        let _ = writeln!(out, "Not defined in the lecture: {}", undefined.join(", "));
+    }
+}
+
+fn text_summary(out: &mut String, report: &AnalysisReport) {
+    out.push_str("Extracted Summary:\n");
+    for line in report.summary_lines() {
+        let _ = writeln!(out, "- {}", line);
+    }
+}
+
+fn text_sections(out: &mut String, report: &AnalysisReport) {
+    if report.sections.is_empty() {
+        return;
+    }
+    out.push_str("Section Summaries:\n");
+    for section in &report.sections {
+        let _ = writeln!(out, "- {} ({})", section.title, format_page_range(section.start_page, section.end_page));
+        for sentence in &section.summary {
+            let _ = writeln!(out, "    - {} ({})", sentence.text, format_pages(&[sentence.page]));
+        }
+    }
+}
+
+fn text_blocks(out: &mut String, report: &AnalysisReport) {
+    if report.blocks.is_empty() {
+        return;
+    }
+    out.push_str("Code, Tables and Formulas:\n");
+    for line in report.block_lines() {
+        let _ = writeln!(out, "{}", line);
+    }
+}
+
+fn text(out: &mut String, report: &AnalysisReport, view: View) {
+    match view {
+        View::Analyze => {
+            text_details(out, report);
+            if !report.glossary.is_empty() {
+                text_glossary(out, report);
+            }
+            let _ = writeln!(out, "Suggested Resources (count: {}):", report.resources.len());
+            for line in report.resource_lines() {
+                let _ = writeln!(out, "- {}", line);
+            }
+            text_blocks(out, report);
+        }
+        View::Keywords => {
+            let _ = writeln!(out, "Extracted Keywords (language: {:?}):", report.source.language);
+            for line in report.keyword_lines() {
+                let _ = writeln!(out, "- {}", line);
+            }
+        }
+        View::Glossary => text_glossary(out, report),
+        View::Summary => {
+            text_details(out, report);
+            text_summary(out, report);
+            text_sections(out, report);
+            text_blocks(out, report);
+        }
+        View::Resources => {
+            out.push_str("Suggested Resources:\n");
+            for line in report.resource_lines() {
+                let _ = writeln!(out, "- {}", line);
+            }
+        }
+        View::Entities => text_entities(out, report),
+        View::Full => {
+            text_details(out, report);
+            text(out, report, View::Keywords);
+            if !report.glossary.is_empty() {
+                text_glossary(out, report);
+            }
+            text_summary(out, report);
+            text_sections(out, report);
+            if !report.entities.is_empty() {
+                text_entities(out, report);
+            }
+            if !report.resources.is_empty() {
+                text(out, report, View::Resources);
+            }
+            text_blocks(out, report);
+        }
+    }
+}
+
+fn text_entities(out: &mut String, report: &AnalysisReport) {
+    out.push_str("Extracted Entities:\n");
+    for entity in &report.entities {
+        let mut pages: Vec<u32> = entity.mentions.iter().map(|m| m.page).collect();
+        pages.dedup();
+        let _ = writeln!(out, "Entity: {}, Label: {}, Confidence: {:.3}, Count: {} ({})",
+            entity.text, entity.label, entity.confidence, entity.count, format_pages(&pages));
+    }
+}
+
+fn markdown_removed(out: &mut String, report: &AnalysisReport) {
+    out.push_str("## Removed Lines\n\n");
+    if report.removed.is_empty() {
+        out.push_str("None\n");
+    }
+    for line in &report.removed {
+        let _ = writeln!(out, "- `{}` ({:?}, {})", line.text, line.reason, format_pages(&line.pages));
+    }
+    out.push('\n');
+}
+
+//Table cells may not contain pipes or line breaks
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+//Every part the subcommand computed (empty parts are left out), in the order of the exported PDF
+fn markdown(out: &mut String, report: &AnalysisReport) {
+    let title: &str = report.source.metadata.title.as_deref().unwrap_or(&report.source.file);
+    let _ = writeln!(out, "# {}\n", title);
+    for line in report.detail_lines() {
+        let _ = writeln!(out, "- {}", line);
+    }
+    out.push('\n');
+
+    if !report.source.outline.is_empty() {
+        out.push_str("## Outline\n\n");
+        for line in report.outline_lines() {
+            let _ = writeln!(out, "{}", line);
+        }
+        out.push('\n');
+    }
+    if !report.keywords.is_empty() {
+        let _ = writeln!(out, "## Keywords\n\nLanguage: {:?}\n", report.source.language);
+        for line in report.keyword_lines() {
+            let _ = writeln!(out, "- {}", line);
+        }
+        out.push('\n');
+    }
+    if !report.glossary.is_empty() {
+        out.push_str("## Glossary\n\n");
+        for entry in &report.glossary {
+            if let (Some(definition), Some(page)) = (&entry.definition, entry.page) {
+                let _ = writeln!(out, "- **{}** ({}): {}", entry.term, format_pages(&[page]), definition);
+            }
+        }
+        let undefined: Vec<&str> = report.undefined_terms();
+        if !undefined.is_empty() {
+            //a paragraph of its own after the list
+            if undefined.len() < report.glossary.len() {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "Not defined in the lecture: {}", undefined.join(", "));
+        }
+        out.push('\n');
+    }
+    if !report.summary.is_empty() {
+        out.push_str("## Summary\n\n");
+        for line in report.summary_lines() {
+            let _ = writeln!(out, "- {}", line);
+        }
+        out.push('\n');
+    }
+    if !report.sections.is_empty() {
+        out.push_str("## Section Summaries\n\n");
+        for section in &report.sections {
+            let _ = writeln!(out, "### {} ({})\n", section.title, format_page_range(section.start_page, section.end_page));
+            for sentence in &section.summary {
+                let _ = writeln!(out, "- {} ({})", sentence.text, format_pages(&[sentence.page]));
+            }
+            out.push('\n');
+        }
+    }
+    if !report.blocks.is_empty() {
+        out.push_str("## Code, Tables and Formulas\n\n");
+        for block in &report.blocks {
+            match block.kind {
+                BlockKind::Table => {
+                    let _ = writeln!(out, "### Table ({})\n", format_pages(&[block.page]));
+                    let width: usize = block.rows.iter().map(|row| row.len()).max().unwrap_or(0);
+                    for (i, row) in block.rows.iter().enumerate() {
+                        let cells: Vec<String> = (0..width).map(|c| markdown_cell(row.get(c).map_or("", |cell| cell))).collect();
+                        let _ = writeln!(out, "| {} |", cells.join(" | "));
+                        if i == 0 {
+                            let _ = writeln!(out, "|{}", " --- |".repeat(width));
+                        }
+                    }
+                }
+                BlockKind::Math => {
+                    let _ = writeln!(out, "### Formula ({})\n\n```\n{}\n```", format_pages(&[block.page]), block.text);
+                }
+                _ => {
+                    let _ = writeln!(out, "### Code ({})\n\n```\n{}\n```", format_pages(&[block.page]), block.text);
+                }
+            }
+            out.push('\n');
+        }
+    }
+    if !report.entities.is_empty() {
+        out.push_str("## Entities\n\n| Entity | Label | Confidence | Count | Pages |\n| --- | --- | --- | --- | --- |\n");
+        for entity in &report.entities {
+            let mut pages: Vec<u32> = entity.mentions.iter().map(|m| m.page).collect();
+            pages.dedup();
+            let list: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+            let _ = writeln!(out, "| {} | {} | {:.3} | {} | {} |",
+                markdown_cell(&entity.text), entity.label, entity.confidence, entity.count, list.join(", "));
+        }
+        out.push('\n');
+    }
+    if !report.resources.is_empty() {
+        out.push_str("## Resources\n\n");
+        for resource in &report.resources {
+            let _ = writeln!(out, "- [{}]({})", markdown_cell(&resource.title), resource.url);
+        }
+        out.push('\n');
+    }
+}
@@ -1,7 +1,616 @@
-use std::error::Error;
+use clap::Args;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::time::Duration;
+use pdf_extract::{ConvertToFmt, MediaBox, OutputDev, OutputError, PlainTextOutput, Transform};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::clean::CleanArgs;
+use crate::layout;
 
-//Extract text from PDF using pdf-extract crate
-pub fn extract_text(path: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
-    let text: String = pdf_extract::extract_text(path)?;
-    Ok(text)
-}
\ No newline at end of file
+//Extraction options shared by every subcommand that reads lectures
+#[derive(Debug, Clone, Args)]
+pub struct ExtractArgs {
+    //Give up on a PDF after this many seconds (0 waits forever)
+    #[arg(long, default_value_t = 60)]
+    pub extract_timeout: u64,
+    //Rebuild the reading order from glyph positions (two-column handouts and papers)
+    #[arg(long)]
+    pub layout: bool,
+    //Leave code, tables and formulas in the text the analyzers see (they are reported either way)
+    #[arg(long)]
+    pub keep_blocks: bool,
+    #[command(flatten)]
+    pub clean: CleanArgs,
+}
+
+impl ExtractArgs {
+    pub fn timeout(&self) -> Option<Duration> {
+        (self.extract_timeout > 0).then(|| Duration::from_secs(self.extract_timeout))
+    }
+}
+
+//1-based inclusive page ranges parsed from "1-5,9"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRanges(pub Vec<(u32, u32)>);
+
+impl PageRanges {
+    pub fn contains(&self, page: u32) -> bool {
+        self.0.iter().any(|&(start, end)| start <= page && page <= end)
+    }
+}
+
+impl FromStr for PageRanges {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let number = |part: &str| match part.trim().parse::<u32>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("invalid page number {:?} (pages start at 1)", part.trim())),
+        };
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        for part in value.split(',').filter(|p| !p.trim().is_empty()) {
+            let range: (u32, u32) = match part.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                None => (number(part)?, number(part)?),
+            };
+            if range.0 > range.1 {
+                return Err(format!("page range {:?} runs backwards", part.trim()));
+            }
+            ranges.push(range);
+        }
+        if ranges.is_empty() {
+            return Err("no pages given, expected ranges like 1-5,9".to_string());
+        }
+        Ok(PageRanges(ranges))
+    }
+}
+
+//Which pages of the lecture to analyze; with both options only pages in both are used
+#[derive(Debug, Clone, Default, Args)]
+pub struct PageArgs {
+    //Page ranges like 1-5,9 (1-based, inclusive)
+    #[arg(long)]
+    pub pages: Option<PageRanges>,
+    //Only the pages of this outline section (bookmark title, case-insensitive; a part of it is enough)
+    #[arg(long)]
+    pub section: Option<String>,
+}
+
+//Why a PDF could not be read; broken uploads end up here instead of crashing the tool
+#[derive(Debug, Error)]
+pub enum ExtractError {
+    #[error("not a readable PDF: {0}")]
+    Open(#[from] lopdf::Error),
+    #[error("text extraction crashed: {0}")]
+    Panicked(String),
+    #[error("text extraction took longer than {0:?}")]
+    TimedOut(Duration),
+    //--pages/--section picked nothing
+    #[error("{0}")]
+    Selection(String),
+    #[error("could not read file: {0}")]
+    Read(#[from] std::io::Error),
+    //broken .pptx archive or unknown file type
+    #[error("{0}")]
+    Format(String),
+}
+
+//One page of the lecture, number is 1-based like the page labels in a PDF viewer
+#[derive(Debug, Clone, Serialize)]
+pub struct Page {
+    pub number: u32,
+    pub text: String,
+}
+
+//Info dictionary fields we report; missing ones stay None
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    //"2024-02-19 10:15:00" (from the PDF date "D:20240219101500+08'00'")
+    pub creation_date: Option<String>,
+    pub page_count: usize,
+}
+
+//One bookmark of the PDF outline; level 1 is a chapter, 2 a section in it, ...
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineEntry {
+    pub level: usize,
+    pub title: String,
+    pub page: u32,
+}
+
+//Outline entry (or run of continued entries) with the pages it covers, up to the next section
+#[derive(Debug, Clone, Serialize)]
+pub struct Section {
+    pub title: String,
+    pub start_page: u32,
+    pub end_page: u32,
+}
+
+//What a run of lines on a page holds; only prose and bullets are left in the text the analyzers see
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Prose,
+    Bullet,
+    Code,
+    Table,
+    Math,
+}
+
+//Code listing, table or formula taken out of a page, text as it was extracted (indentation kept)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub page: u32,
+    pub text: String,
+    //cells of each row, tables only
+    pub rows: Vec<Vec<String>>,
+}
+
+//Page-aware lecture text so analyzers can say where something came from
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Document {
+    pub pages: Vec<Page>,
+    pub metadata: Metadata,
+    pub outline: Vec<OutlineEntry>,
+    //code, tables and formulas found by blocks::separate_blocks, in page order
+    pub blocks: Vec<Block>,
+}
+
+impl Document {
+    //Whole lecture as one string (pages separated by a blank line)
+    pub fn text(&self) -> String {
+        self.pages
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n\n")
+    }
+
+    //Pages whose text mentions the term (case-insensitive)
+    pub fn pages_containing(&self, term: &str) -> Vec<u32> {
+        let term: String = term.to_lowercase();
+        self.pages
+            .iter()
+            .filter(|p| p.text.to_lowercase().contains(&term))
+            .map(|p| p.number)
+            .collect()
+    }
+
+    //Sections from the outline in page order, empty without an outline
+    pub fn sections(&self) -> Vec<Section> {
+        outline_sections(&self.outline, self.pages.last().map(|p| p.number).unwrap_or(0))
+    }
+
+    //Keep only the pages picked by --pages/--section (for formats read whole, unlike PDF)
+    pub fn select(mut self, selection: &PageArgs) -> Result<Document, ExtractError> {
+        let page_numbers: Vec<u32> = self.pages.iter().map(|p| p.number).collect();
+        let selected: Vec<u32> = select_page_numbers(&page_numbers, &self.outline, selection)?;
+        self.pages.retain(|p| selected.contains(&p.number));
+        self.outline.retain(|e| selected.contains(&e.page));
+        self.blocks.retain(|b| selected.contains(&b.page));
+        Ok(self)
+    }
+
+    //Just the pages from start to end (inclusive), same metadata
+    pub fn page_range(&self, start: u32, end: u32) -> Document {
+        Document {
+            pages: self.pages.iter().filter(|p| p.number >= start && p.number <= end).cloned().collect(),
+            metadata: self.metadata.clone(),
+            outline: self.outline.iter().filter(|e| e.page >= start && e.page <= end).cloned().collect(),
+            blocks: self.blocks.iter().filter(|b| b.page >= start && b.page <= end).cloned().collect(),
+        }
+    }
+}
+
+//Sections of an outline: the shallowest level with more than one entry (PowerPoint exports put
+//every slide under one root bookmark), neighbours that only differ by "(continued)" or a slide
+//number merged, each running until the next one starts
+fn outline_sections(outline: &[OutlineEntry], last_page: u32) -> Vec<Section> {
+    let mut levels: Vec<usize> = outline.iter().map(|e| e.level).collect();
+    levels.sort();
+    levels.dedup();
+    let Some(level) = levels
+        .iter()
+        .find(|&&l| outline.iter().filter(|e| e.level == l).count() > 1)
+        .or(levels.first())
+    else {
+        return Vec::new();
+    };
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut previous_key: String = String::new();
+    for entry in outline.iter().filter(|e| e.level == *level) {
+        let key: String = section_key(&entry.title);
+        match sections.last_mut() {
+            Some(section) if key == previous_key => section.end_page = entry.page,
+            _ => sections.push(Section { title: entry.title.clone(), start_page: entry.page, end_page: entry.page }),
+        }
+        previous_key = key;
+    }
+    let starts: Vec<u32> = sections.iter().skip(1).map(|s| s.start_page).chain([last_page + 1]).collect();
+    for (section, next) in sections.iter_mut().zip(starts) {
+        section.end_page = next.saturating_sub(1).max(section.end_page).max(section.start_page);
+    }
+    sections
+}
+
+//Page numbers picked by --pages/--section (both given: pages in both)
+pub fn select_page_numbers(page_numbers: &[u32], outline: &[OutlineEntry], selection: &PageArgs) -> Result<Vec<u32>, ExtractError> {
+    let last_page: u32 = page_numbers.iter().copied().max().unwrap_or(0);
+    let section: Option<Section> = match &selection.section {
+        Some(_) if outline.is_empty() => {
+            return Err(ExtractError::Selection("--section needs a lecture with bookmarks (outline), this one has none".to_string()));
+        }
+        Some(query) => Some(
+            find_section(outline, query, last_page)
+                .ok_or_else(|| ExtractError::Selection(format!("no outline section matches {:?}", query)))?,
+        ),
+        None => None,
+    };
+    let selected: Vec<u32> = page_numbers
+        .iter()
+        .copied()
+        .filter(|&n| selection.pages.as_ref().is_none_or(|ranges| ranges.contains(n)))
+        .filter(|&n| section.as_ref().is_none_or(|s| s.start_page <= n && n <= s.end_page))
+        .collect();
+    if selected.is_empty() {
+        return Err(ExtractError::Selection(format!("no pages selected (the lecture has {} pages)", page_numbers.len())));
+    }
+    Ok(selected)
+}
+
+//Outline entry picked with --section (exact title first, then the first title containing the query)
+//and its pages: up to the next entry at the same or a higher level, continued entries included
+fn find_section(outline: &[OutlineEntry], query: &str, last_page: u32) -> Option<Section> {
+    let query: String = query.trim().to_lowercase();
+    let index: usize = outline
+        .iter()
+        .position(|e| e.title.to_lowercase() == query)
+        .or_else(|| outline.iter().position(|e| e.title.to_lowercase().contains(&query)))?;
+    let entry: &OutlineEntry = &outline[index];
+    let key: String = section_key(&entry.title);
+
+    let mut end_page: u32 = last_page;
+    for next in outline[index + 1..].iter().filter(|e| e.level <= entry.level) {
+        if next.level == entry.level && section_key(&next.title) == key {
+            continue;
+        }
+        end_page = next.page.saturating_sub(1).max(entry.page);
+        break;
+    }
+    Some(Section { title: entry.title.clone(), start_page: entry.page, end_page })
+}
+
+//Outline title without "(continued)", numbers and case, so continued slides compare equal
+fn section_key(title: &str) -> String {
+    title
+        .to_lowercase()
+        .replace("(continued)", "")
+        .replace(|c: char| c.is_ascii_digit(), "")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+//PDF text strings are UTF-16BE with a byte order mark or PDFDocEncoding (close enough to Latin-1)
+fn decode_text_string(bytes: &[u8]) -> String {
+    let text: String = match bytes {
+        [0xfe, 0xff, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|b| u16::from_be_bytes([b[0], b[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => bytes.iter().map(|&b| b as char).collect(),
+    };
+    text.trim_matches(char::from(0)).trim().to_string()
+}
+
+//"D:20240219101500+08'00'" -> "2024-02-19 10:15:00" (as much of it as the PDF gives)
+fn format_pdf_date(date: &str) -> String {
+    let digits: String = date.trim_start_matches("D:").chars().take_while(|c| c.is_ascii_digit()).collect();
+    let part = |from: usize, to: usize| digits.get(from..to);
+    match (part(0, 4), part(4, 6), part(6, 8)) {
+        (Some(year), Some(month), Some(day)) => match (part(8, 10), part(10, 12), part(12, 14)) {
+            (Some(hour), Some(minute), second) => {
+                format!("{}-{}-{} {}:{}:{}", year, month, day, hour, minute, second.unwrap_or("00"))
+            }
+            _ => format!("{}-{}-{}", year, month, day),
+        },
+        _ => date.to_string(),
+    }
+}
+
+//Title, author, subject and creation date from the trailer's Info dictionary
+fn read_metadata(pdf: &lopdf::Document) -> Metadata {
+    let mut metadata: Metadata = Metadata { page_count: pdf.get_pages().len(), ..Default::default() };
+    let Ok(info) = pdf.trailer.get_deref(b"Info", pdf).and_then(|o| o.as_dict()) else {
+        return metadata;
+    };
+    let field = |key: &[u8]| {
+        info.get_deref(key, pdf)
+            .and_then(|o| o.as_str())
+            .ok()
+            .map(decode_text_string)
+            .filter(|value| !value.is_empty())
+    };
+    metadata.title = field(b"Title");
+    metadata.author = field(b"Author");
+    metadata.subject = field(b"Subject");
+    metadata.creation_date = field(b"CreationDate").map(|d| format_pdf_date(&d));
+    metadata
+}
+
+//Bookmark tree flattened in page order (lopdf returns it sorted by title); empty without one
+fn read_outline(pdf: &lopdf::Document) -> Vec<OutlineEntry> {
+    let Ok(toc) = pdf.get_toc() else {
+        return Vec::new();
+    };
+    let mut outline: Vec<OutlineEntry> = toc
+        .toc
+        .into_iter()
+        .map(|entry| OutlineEntry {
+            level: entry.level,
+            title: entry.title.trim_matches(char::from(0)).split_whitespace().collect::<Vec<&str>>().join(" "),
+            page: entry.page as u32,
+        })
+        .collect();
+    outline.sort_by_key(|e| (e.page, e.level));
+    outline
+}
+
+//Message of a caught panic (panic!("...") gives a &str or a String)
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map(|m| m.to_string()).unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+//Text written by pdf-extract's PlainTextOutput or our LayoutOutput, shared with the PageSplitter that
+//takes it out at the end of every page
+#[derive(Clone, Default)]
+struct SharedText(Rc<RefCell<String>>);
+
+impl fmt::Write for SharedText {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.borrow_mut().push_str(s);
+        Ok(())
+    }
+}
+
+impl ConvertToFmt for SharedText {
+    type Writer = SharedText;
+    fn convert(self) -> Self::Writer {
+        self
+    }
+}
+
+//Page callback around an OutputDev: everything it writes between begin_page and end_page is that page's text
+struct PageSplitter {
+    output: Box<dyn OutputDev>,
+    text: SharedText,
+    pages: Vec<String>,
+}
+
+impl PageSplitter {
+    //in content stream order, or (with `layout`) in the reading order rebuilt from glyph positions
+    fn new(layout: bool) -> Self {
+        let text: SharedText = SharedText::default();
+        let output: Box<dyn OutputDev> = if layout {
+            Box::new(layout::LayoutOutput::new(text.clone()))
+        } else {
+            Box::new(PlainTextOutput::new(text.clone()))
+        };
+        PageSplitter { output, text, pages: Vec::new() }
+    }
+}
+
+impl OutputDev for PageSplitter {
+    fn begin_page(&mut self, page_num: u32, media_box: &MediaBox, art_box: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.text.0.borrow_mut().clear();
+        self.output.begin_page(page_num, media_box, art_box)
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        self.output.end_page()?;
+        self.pages.push(self.text.0.take());
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, spacing: f64, font_size: f64, char: &str) -> Result<(), OutputError> {
+        self.output.output_character(trm, width, spacing, font_size, char)
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        self.output.begin_word()
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        self.output.end_word()
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        self.output.end_line()
+    }
+}
+
+//pdf-extract over the selected pages of the PDF in `buffer`, parsed once. pdf-extract stops at the first
+//page it cannot handle (and panics on some fonts): that page gets the error and the pass resumes after it,
+//with the pages already done deleted from the parsed document
+fn extract_page_texts(buffer: &[u8], selected: &[u32], page_numbers: &[u32], layout: bool) -> HashMap<u32, Result<String, String>> {
+    let mut texts: HashMap<u32, Result<String, String>> = HashMap::new();
+    let mut pdf: extract_lopdf::Document = match extract_lopdf::Document::load_mem(buffer) {
+        Ok(pdf) => pdf,
+        Err(e) => return selected.iter().map(|&number| (number, Err(e.to_string()))).collect(),
+    };
+    let others: Vec<u32> = page_numbers.iter().cloned().filter(|n| !selected.contains(n)).collect();
+    pdf.delete_pages(&others);
+
+    //numbers (in the original PDF) of the pages still in `pdf`, in order
+    let mut remaining: Vec<u32> = selected.to_vec();
+    while !remaining.is_empty() {
+        let mut splitter: PageSplitter = PageSplitter::new(layout);
+        let error: Option<String> = match panic::catch_unwind(AssertUnwindSafe(|| pdf_extract::output_doc(&pdf, &mut splitter))) {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(payload) => Some(panic_message(payload)),
+        };
+        let done: usize = splitter.pages.len();
+        texts.extend(remaining.iter().cloned().zip(splitter.pages.into_iter().map(Ok)));
+        match error {
+            Some(error) if done < remaining.len() => {
+                texts.insert(remaining[done], Err(error));
+                pdf.delete_pages(&(1..=done as u32 + 1).collect::<Vec<u32>>());
+                remaining.drain(..=done);
+            }
+            _ => break,
+        }
+    }
+    texts
+}
+
+//Fallback when pdf-extract fails: lopdf's own decoder (no Identity-H/ToUnicode support, so
+//it may miss text, but it rarely fails outright)
+fn extract_page_raw(pdf: &lopdf::Document, number: u32) -> String {
+    match panic::catch_unwind(AssertUnwindSafe(|| pdf.extract_text(&[number]))) {
+        Ok(Ok(text)) => text.replace("?Identity-H Unimplemented?", ""),
+        _ => String::new(),
+    }
+}
+
+//Extract text per page in one pass (pdf-extract decodes Identity-H/ToUnicode fonts, lopdf does not);
+//pages pdf-extract cannot handle fall back to lopdf's raw text
+fn extract_pages(path: &str, layout: bool, selection: &PageArgs) -> Result<Document, ExtractError> {
+    let buffer: Vec<u8> = std::fs::read(path)?;
+    let pdf: lopdf::Document = lopdf::Document::load_mem(&buffer)?;
+    let page_numbers: Vec<u32> = pdf.get_pages().keys().cloned().collect();
+    let mut outline: Vec<OutlineEntry> = read_outline(&pdf);
+
+    //only the selected pages are extracted
+    let selected: Vec<u32> = select_page_numbers(&page_numbers, &outline, selection)?;
+    outline.retain(|e| selected.contains(&e.page));
+
+    let mut texts: HashMap<u32, Result<String, String>> = extract_page_texts(&buffer, &selected, &page_numbers, layout);
+    let mut pages: Vec<Page> = Vec::new();
+    for &number in &selected {
+        let text: String = match texts.remove(&number).unwrap_or_else(|| Err("page not reached".to_string())) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("Page {}: pdf-extract failed ({}), using raw text", number, e);
+                extract_page_raw(&pdf, number)
+            }
+        };
+        pages.push(Page { number, text: text.trim().to_string() });
+    }
+    Ok(Document { pages, metadata: read_metadata(&pdf), outline, blocks: Vec::new() })
+}
+
+//Extract a lecture (only the pages picked by `selection`, in layout reading order with `layout`) on a
+//worker thread so a panic anywhere in the PDF libraries becomes an ExtractError and a PDF that never finishes is abandoned after `timeout` (None waits forever).
+//A timed-out worker cannot be killed; it keeps running in the background until it ends
+pub fn extract_document(path: &str, timeout: Option<Duration>, layout: bool, selection: &PageArgs) -> Result<Document, ExtractError> {
+    let (sender, receiver) = mpsc::channel();
+    let path: String = path.to_string();
+    let selection: PageArgs = selection.clone();
+    std::thread::spawn(move || {
+        let result: Result<Document, ExtractError> = panic::catch_unwind(AssertUnwindSafe(|| extract_pages(&path, layout, &selection)))
+            .unwrap_or_else(|payload| Err(ExtractError::Panicked(panic_message(payload))));
+        let _ = sender.send(result);
+    });
+
+    match timeout {
+        Some(limit) => match receiver.recv_timeout(limit) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(ExtractError::TimedOut(limit)),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(ExtractError::Panicked("extraction thread stopped".to_string())),
+        },
+        None => receiver
+            .recv()
+            .unwrap_or_else(|_| Err(ExtractError::Panicked("extraction thread stopped".to_string()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(value: &str) -> Result<Vec<(u32, u32)>, String> {
+        value.parse::<PageRanges>().map(|r| r.0)
+    }
+
+    #[test]
+    fn parses_page_ranges() {
+        assert_eq!(ranges("1-5,9"), Ok(vec![(1, 5), (9, 9)]));
+        assert_eq!(ranges(" 2 , 4 - 4 ,"), Ok(vec![(2, 2), (4, 4)]));
+        let parsed: PageRanges = "1-5,9".parse().unwrap();
+        assert!(parsed.contains(1) && parsed.contains(5) && parsed.contains(9));
+        assert!(!parsed.contains(6) && !parsed.contains(10));
+    }
+
+    #[test]
+    fn rejects_reversed_empty_and_zero_ranges() {
+        assert!(ranges("5-1").unwrap_err().contains("runs backwards"));
+        assert!(ranges("").unwrap_err().contains("no pages given"));
+        assert!(ranges(" , ").unwrap_err().contains("no pages given"));
+        assert!(ranges("0").unwrap_err().contains("pages start at 1"));
+        assert!(ranges("0-3").unwrap_err().contains("pages start at 1"));
+        assert!(ranges("3-").is_err());
+        assert!(ranges("two").is_err());
+    }
+
+    fn outline() -> Vec<OutlineEntry> {
+        [(1, "Introduction", 1), (1, "Outer joins", 3), (1, "Joins", 5), (1, "Joins (continued)", 6), (2, "Hash join", 6), (1, "Summary", 8)]
+            .into_iter()
+            .map(|(level, title, page)| OutlineEntry { level, title: title.to_string(), page })
+            .collect()
+    }
+
+    fn section(query: &str) -> Option<(String, u32, u32)> {
+        find_section(&outline(), query, 10).map(|s| (s.title, s.start_page, s.end_page))
+    }
+
+    #[test]
+    fn exact_section_titles_win_over_substrings() {
+        assert_eq!(section("joins"), Some(("Joins".to_string(), 5, 7)));
+        assert_eq!(section("JOIN"), Some(("Outer joins".to_string(), 3, 4)));
+        assert_eq!(section("hash join"), Some(("Hash join".to_string(), 6, 7)));
+        assert_eq!(section("summary"), Some(("Summary".to_string(), 8, 10)));
+        assert_eq!(section("normalization"), None);
+    }
+
+    #[test]
+    fn continued_entries_are_merged_into_one_section() {
+        let sections: Vec<(String, u32, u32)> = outline_sections(&outline(), 10).into_iter().map(|s| (s.title, s.start_page, s.end_page)).collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("Introduction".to_string(), 1, 2),
+                ("Outer joins".to_string(), 3, 4),
+                ("Joins".to_string(), 5, 7),
+                ("Summary".to_string(), 8, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn pages_and_section_select_the_pages_in_both() {
+        let pages: Vec<u32> = (1..=10).collect();
+        let selection: PageArgs = PageArgs { pages: Some("1-6".parse().unwrap()), section: Some("joins".to_string()) };
+        assert_eq!(select_page_numbers(&pages, &outline(), &selection).unwrap(), vec![5, 6]);
+
+        let selection: PageArgs = PageArgs { pages: None, section: Some("joins".to_string()) };
+        assert!(matches!(select_page_numbers(&pages, &[], &selection), Err(ExtractError::Selection(_))));
+        let selection: PageArgs = PageArgs { pages: Some("11-12".parse().unwrap()), section: None };
+        assert!(matches!(select_page_numbers(&pages, &outline(), &selection), Err(ExtractError::Selection(_))));
+    }
+}
@@ -0,0 +1,358 @@
+//One report per analyzed lecture: `analyze_lecture` is the only place that runs the analyzers, and
+//every CLI subcommand, the HTTP handler and the PDF exporter read its AnalysisReport, so their
+//outputs cannot drift apart
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use crate::abstractive::LazySummarizer;
+use crate::analyze::{self, KeywordLocation, SectionSummary, SummaryMethod, SummarySentence};
+use crate::blocks;
+use crate::cache::ResultCache;
+use crate::clean::{self, RemovedLine};
+use crate::glossary::{self, GlossaryEntry};
+use crate::input;
+use crate::keywords::KeywordExtractor;
+use crate::lang::{Lang, StopwordConfig, StopwordList};
+use crate::ner::{self, AggregatedEntity, NERModel};
+use crate::normalize;
+use crate::pdf::{Block, BlockKind, Document, ExtractArgs, ExtractError, Metadata, OutlineEntry, PageArgs};
+use crate::utils::{self, Resource};
+
+//Where the report came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Source {
+    //path on the CLI, upload name on the server
+    pub file: String,
+    pub metadata: Metadata,
+    pub outline: Vec<OutlineEntry>,
+    //pages analyzed (all of them unless --pages/--section picked some)
+    pub pages: Vec<u32>,
+    //stopword language used (detected for --lang auto)
+    pub language: Lang,
+}
+
+//Wall-clock time of each stage in milliseconds, 0 for stages that did not run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timings {
+    pub extraction_ms: u64,
+    pub keywords_ms: u64,
+    #[serde(default)]
+    pub glossary_ms: u64,
+    pub summary_ms: u64,
+    pub entities_ms: u64,
+    pub resources_ms: u64,
+    pub total_ms: u64,
+}
+
+//Analyzer behind one stage of the report ("keywords", "rake", "0.1.0")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzerVersion {
+    pub stage: String,
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    pub source: Source,
+    pub keywords: Vec<KeywordLocation>,
+    //a definition per keyword, none where the lecture gives none
+    #[serde(default)]
+    pub glossary: Vec<GlossaryEntry>,
+    pub summary: Vec<SummarySentence>,
+    //per outline section, empty without bookmarks/headings
+    pub sections: Vec<SectionSummary>,
+    pub entities: Vec<AggregatedEntity>,
+    pub resources: Vec<Resource>,
+    //headers/footers and page numbers stripped before analysis
+    pub removed: Vec<RemovedLine>,
+    //code listings, tables and formulas kept out of the analysis
+    pub blocks: Vec<Block>,
+    pub timings: Timings,
+    pub analyzers: Vec<AnalyzerVersion>,
+    //served from the result cache (timings are those of the run that filled it)
+    #[serde(default)]
+    pub cached: bool,
+}
+
+//What to read and how much to summarize; which stages run depends on the Analyzers given
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    pub extract_args: ExtractArgs,
+    pub page_args: PageArgs,
+    pub stopwords: StopwordConfig,
+    //0 skips the summary / the section summaries
+    pub summary_sentences: usize,
+    pub section_sentences: usize,
+    pub summarizer: SummaryMethod,
+    //find a definition in the lecture for each keyword
+    pub glossary: bool,
+    //look up Wikipedia articles for the keywords
+    pub resources: bool,
+    //reuse reports of the same content and settings, none with --no-cache
+    pub cache: Option<ResultCache>,
+}
+
+//Extractor and models for the stages that need them; a stage without one is skipped.
+//The server shares one set between all uploads, each is only locked while its stage runs
+#[derive(Clone, Default)]
+pub struct Analyzers {
+    pub keywords: Option<Arc<Mutex<Box<dyn KeywordExtractor>>>>,
+    pub summarizer: Option<Arc<Mutex<LazySummarizer>>>,
+    pub entities: Option<Arc<Mutex<NERModel>>>,
+}
+
+impl Analyzers {
+    pub fn new(keywords: Option<Box<dyn KeywordExtractor>>, summarizer: Option<LazySummarizer>, entities: Option<NERModel>) -> Self {
+        Analyzers {
+            keywords: keywords.map(|k| Arc::new(Mutex::new(k))),
+            summarizer: summarizer.map(|s| Arc::new(Mutex::new(s))),
+            entities: entities.map(|m| Arc::new(Mutex::new(m))),
+        }
+    }
+}
+
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+fn analyzer(stage: &str, name: &str, version: String) -> AnalyzerVersion {
+    AnalyzerVersion { stage: stage.to_string(), name: name.to_string(), version }
+}
+
+//Extract a lecture (only the --pages/--section pages), strip its headers/footers unless --no-clean,
+//take code, tables and formulas out of the text unless --keep-blocks, then normalize the text for the analyzers
+pub fn load_document(path: &str, extract_args: &ExtractArgs, page_args: &PageArgs) -> Result<(Document, Vec<RemovedLine>), ExtractError> {
+    let mut document: Document = input::extract_document(path, extract_args.timeout(), extract_args.layout, page_args)?;
+    let removed: Vec<RemovedLine> = if extract_args.clean.no_clean {
+        Vec::new()
+    } else {
+        clean::remove_boilerplate(&mut document).removed
+    };
+    blocks::separate_blocks(&mut document, extract_args.keep_blocks);
+    normalize::normalize_document(&mut document);
+    Ok((document, removed))
+}
+
+//The analysis pipeline: load the lecture at `path` (`file` is the name shown in the report), then
+//keywords, glossary, summaries, entities and resources for the stages that have an analyzer
+pub async fn analyze_lecture(path: &str, file: &str, options: &AnalysisOptions, analyzers: &Analyzers) -> Result<AnalysisReport, Box<dyn Error + Send + Sync>> {
+    let started: Instant = Instant::now();
+    let mut timings: Timings = Timings::default();
+    let version: String = env!("CARGO_PKG_VERSION").to_string();
+    let extractor_name: &str = if options.extract_args.layout { "layout" } else { "stream" };
+    let mut versions: Vec<AnalyzerVersion> = vec![analyzer("extraction", extractor_name, version.clone())];
+
+    //a report for the same content and settings skips every stage
+    let mut cache_key: Option<String> = None;
+    if let Some(cache) = &options.cache {
+        let key: String = cache.key(&std::fs::read(path).map_err(ExtractError::from)?, options, analyzers);
+        if let Some(mut report) = cache.load(&key) {
+            report.source.file = file.to_string();
+            report.cached = true;
+            return Ok(report);
+        }
+        cache_key = Some(key);
+    }
+
+    //extraction blocks (up to the timeout), so keep it off the async workers
+    let (path, extract_args, page_args) = (path.to_string(), options.extract_args.clone(), options.page_args.clone());
+    let (document, removed) = tokio::task::spawn_blocking(move || load_document(&path, &extract_args, &page_args)).await??;
+    timings.extraction_ms = elapsed_ms(started);
+    let stopwords: StopwordList = options.stopwords.for_text(&document.text());
+
+    let mut keywords: Vec<(String, f64)> = Vec::new();
+    if let Some(extractor) = &analyzers.keywords {
+        let stage: Instant = Instant::now();
+        let mut extractor = extractor.lock().await;
+        keywords = extractor.extract(&document, &stopwords);
+        versions.push(analyzer("keywords", extractor.name(), extractor.version()));
+        timings.keywords_ms = elapsed_ms(stage);
+    }
+    let keyword_texts: Vec<String> = keywords.iter().map(|(keyword, _)| keyword.clone()).collect();
+
+    let mut glossary: Vec<GlossaryEntry> = Vec::new();
+    if options.glossary && !keyword_texts.is_empty() {
+        let stage: Instant = Instant::now();
+        glossary = glossary::build_glossary(&document, &keyword_texts);
+        versions.push(analyzer("glossary", "patterns", version.clone()));
+        timings.glossary_ms = elapsed_ms(stage);
+    }
+
+    let mut summary: Vec<SummarySentence> = Vec::new();
+    let mut sections: Vec<SectionSummary> = Vec::new();
+    if let Some(summarizer) = &analyzers.summarizer {
+        let stage: Instant = Instant::now();
+        let mut summarizer = summarizer.lock().await;
+        if options.summary_sentences > 0 {
+            summary = analyze::summarize(&document, options.summary_sentences, &keyword_texts, options.summarizer, &mut summarizer)?;
+        }
+        if options.section_sentences > 0 {
+            sections = analyze::summarize_sections(&document, options.section_sentences, &keyword_texts, options.summarizer, &mut summarizer)?;
+        }
+        let summarizer_version: String = match options.summarizer {
+            SummaryMethod::Abstractive => summarizer.version(),
+            _ => version.clone(),
+        };
+        versions.push(analyzer("summary", &format!("{:?}", options.summarizer).to_lowercase(), summarizer_version));
+        timings.summary_ms = elapsed_ms(stage);
+    }
+
+    let mut entities: Vec<AggregatedEntity> = Vec::new();
+    if let Some(model) = &analyzers.entities {
+        let stage: Instant = Instant::now();
+        entities = analyze::extract_entities_aggregated(&mut *model.lock().await, &document);
+        versions.push(analyzer("entities", "ner", ner::MODEL_VERSION.to_string()));
+        timings.entities_ms = elapsed_ms(stage);
+    }
+
+    let mut resources: Vec<Resource> = Vec::new();
+    let mut failed_lookups: usize = 0;
+    if options.resources {
+        let stage: Instant = Instant::now();
+        (resources, failed_lookups) = utils::suggest_resources(&keyword_texts).await?;
+        versions.push(analyzer("resources", "wikipedia", version.clone()));
+        timings.resources_ms = elapsed_ms(stage);
+    }
+    timings.total_ms = elapsed_ms(started);
+
+    let report: AnalysisReport = AnalysisReport {
+        source: Source {
+            file: file.to_string(),
+            metadata: document.metadata.clone(),
+            outline: document.outline.clone(),
+            pages: document.pages.iter().map(|p| p.number).collect(),
+            language: stopwords.language,
+        },
+        keywords: analyze::locate_keywords(&document, &keywords),
+        glossary,
+        summary,
+        sections,
+        entities,
+        resources,
+        removed,
+        blocks: document.blocks,
+        timings,
+        analyzers: versions,
+        cached: false,
+    };
+    //a cache that cannot be written only costs the next run its shortcut. Resources missing because a
+    //lookup failed (offline) are not stored, or every later run would get the incomplete list
+    if let (Some(cache), Some(key)) = (&options.cache, &cache_key)
+        && failed_lookups == 0
+        && let Err(e) = cache.store(key, &report)
+    {
+        eprintln!("Cannot cache the report of {}: {}", file, e);
+    }
+    Ok(report)
+}
+
+//"page 3" or "pages 3, 7" for CLI and PDF output
+pub fn format_pages(pages: &[u32]) -> String {
+    let list: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+    match pages.len() {
+        0 => "no page".to_string(),
+        1 => format!("page {}", list[0]),
+        _ => format!("pages {}", list.join(", ")),
+    }
+}
+
+//"page 3" or "pages 3-7" for a section
+pub fn format_page_range(start: u32, end: u32) -> String {
+    if start == end {
+        format!("page {}", start)
+    } else {
+        format!("pages {}-{}", start, end)
+    }
+}
+
+//Lines shared by the CLI output and the exported PDF
+impl AnalysisReport {
+    //"Title: ...", "Author: ..." etc. for the fields the lecture has, plus the page count
+    pub fn detail_lines(&self) -> Vec<String> {
+        let metadata: &Metadata = &self.source.metadata;
+        let fields = [
+            ("Title", &metadata.title),
+            ("Author", &metadata.author),
+            ("Subject", &metadata.subject),
+            ("Created", &metadata.creation_date),
+        ];
+        let mut lines: Vec<String> = fields
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| format!("{}: {}", name, v)))
+            .collect();
+        lines.push(format!("Pages: {}", metadata.page_count));
+        lines
+    }
+
+    //Outline entries as "- Title (page N)", indented by level
+    pub fn outline_lines(&self) -> Vec<String> {
+        self.source
+            .outline
+            .iter()
+            .map(|e| format!("{}- {} (page {})", "  ".repeat(e.level.saturating_sub(1)), e.title, e.page))
+            .collect()
+    }
+
+    //"keyword (pages 3, 7)"
+    pub fn keyword_lines(&self) -> Vec<String> {
+        self.keywords.iter().map(|k| format!("{} ({})", k.keyword, format_pages(&k.pages))).collect()
+    }
+
+    //"term (page 3): defining sentence" for the keywords the lecture defines
+    pub fn glossary_lines(&self) -> Vec<String> {
+        self.glossary
+            .iter()
+            .filter_map(|entry| match (&entry.definition, entry.page) {
+                (Some(definition), Some(page)) => Some(format!("{} ({}): {}", entry.term, format_pages(&[page]), definition)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    //Keywords the lecture uses without defining them
+    pub fn undefined_terms(&self) -> Vec<&str> {
+        self.glossary.iter().filter(|e| e.definition.is_none()).map(|e| e.term.as_str()).collect()
+    }
+
+    //"sentence (page 3)"
+    pub fn summary_lines(&self) -> Vec<String> {
+        self.summary.iter().map(|s| format!("{} ({})", s.text, format_pages(&[s.page]))).collect()
+    }
+
+    //One line per section: "Title (pages 3-7): sentence sentence"
+    pub fn section_lines(&self) -> Vec<String> {
+        self.sections
+            .iter()
+            .map(|s| {
+                let text: Vec<&str> = s.summary.iter().map(|sentence| sentence.text.as_str()).collect();
+                format!("{} ({}): {}", s.title, format_page_range(s.start_page, s.end_page), text.join(" "))
+            })
+            .collect()
+    }
+
+    //"Code (page 5):" and the block's lines indented below it (table rows as "cell | cell")
+    pub fn block_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for block in &self.blocks {
+            let label: &str = match block.kind {
+                BlockKind::Table => "Table",
+                BlockKind::Math => "Formula",
+                _ => "Code",
+            };
+            lines.push(format!("{} ({}):", label, format_pages(&[block.page])));
+            match block.kind {
+                BlockKind::Table => lines.extend(block.rows.iter().map(|row| format!("    {}", row.join(" | ")))),
+                _ => lines.extend(block.text.lines().map(|line| format!("    {}", line))),
+            }
+        }
+        lines
+    }
+
+    //"Title - url"
+    pub fn resource_lines(&self) -> Vec<String> {
+        self.resources.iter().map(|r| format!("{} - {}", r.title, r.url)).collect()
+    }
+}
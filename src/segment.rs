@@ -0,0 +1,260 @@
+//Sentence segmentation shared by the summarizers, NER chunking and other analyzers.
+//Handles abbreviations ("e.g.", "Dr."), decimals/URLs (no space after the dot),
+//?/! endings and slide bullets or lines that have no period at all.
+
+//One sentence; start/end are char offsets into the segmented text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sentence {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+//Words that end with a period without ending the sentence (lowercase, without the final '.')
+const ABBREVIATIONS: &[&str] = &[
+    "e.g", "i.e", "etc", "vs", "cf", "approx", "incl", "esp", "resp", "viz",
+    "fig", "figs", "eq", "eqs", "ch", "sec", "ref",
+    "dr", "mr", "mrs", "ms", "prof", "jr", "sr", "dept", "univ", "inc", "ltd",
+    "jan", "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec",
+];
+//Abbreviations only when a number follows ("no. 5", "p. 12", "vol. 3"); "The answer is no." ends a sentence
+const NUMBER_ABBREVIATIONS: &[&str] = &["no", "nos", "p", "pp", "vol"];
+//Abbreviations only in a name: capitalized and followed by a capitalized word ("St. Louis", "Co. Ltd")
+const NAME_ABBREVIATIONS: &[&str] = &["st", "co"];
+//Capitalized words that start a new sentence after "the C." or "vitamin A." rather than continue a name
+const SENTENCE_STARTERS: &[&str] = &[
+    "a", "an", "the", "this", "that", "these", "those", "it", "its", "we", "you", "they", "he", "she", "i", "our",
+    "in", "on", "at", "for", "from", "to", "with", "by", "as", "if", "when", "then", "there", "here", "next", "now",
+    "so", "but", "and", "or", "however", "thus", "therefore", "also", "each", "every", "all", "some", "many", "most",
+    "one", "what", "why", "how", "which", "who", "where", "note", "see", "finally", "first", "second", "let",
+];
+
+//Markers that start a slide bullet (U+F0B7 is the Symbol-font bullet PowerPoint exports)
+pub const BULLETS: &[char] = &['•', '◦', '▪', '▫', '●', '○', '■', '□', '‣', '⁃', '–', '—', '-', '*', '➢', '►', '✓', '\u{f0b7}'];
+
+const TERMINATORS: &[char] = &['.', '?', '!', '…'];
+//Closing quotes/brackets that stay with the sentence they close
+const CLOSERS: &[char] = &['"', '\'', ')', ']', '”', '’', '»'];
+
+//Word right before the period at `dot` (letters, digits and inner dots, e.g. "e.g")
+fn word_before(chars: &[char], dot: usize) -> (usize, String) {
+    let mut k: usize = dot;
+    while k > 0 && (chars[k - 1].is_alphanumeric() || chars[k - 1] == '.') {
+        k -= 1;
+    }
+    (k, chars[k..dot].iter().collect())
+}
+
+//Word right after the period at `dot` (letters and digits) and whether it is itself followed by a period ("J. R. Smith")
+fn word_after(chars: &[char], dot: usize) -> (String, bool) {
+    let mut k: usize = dot + 1;
+    while k < chars.len() && chars[k].is_whitespace() {
+        k += 1;
+    }
+    let start: usize = k;
+    while k < chars.len() && chars[k].is_alphanumeric() {
+        k += 1;
+    }
+    (chars[start..k].iter().collect(), chars.get(k) == Some(&'.'))
+}
+
+//Does a name go on after the period at `dot`: a capitalized word that is not a usual sentence opener, or another initial
+fn name_follows(chars: &[char], dot: usize) -> bool {
+    let (next, dotted) = word_after(chars, dot);
+    if !next.starts_with(char::is_uppercase) {
+        return false;
+    }
+    (dotted && next.chars().count() == 1) || !SENTENCE_STARTERS.contains(&next.to_lowercase().as_str())
+}
+
+//Is the period at `dot` part of an abbreviation, an initial or a list number instead of a sentence end?
+fn is_abbreviation(chars: &[char], dot: usize) -> bool {
+    let (word_start, word) = word_before(chars, dot);
+    if word.is_empty() {
+        return false;
+    }
+    let lower: String = word.to_lowercase();
+    //"e.g", "i.e", "U.S": every dotted part one letter (not "3.14", "example.com" or "1.2.3")
+    let initialism: bool = word.contains('.') && word.split('.').all(|part| part.chars().count() == 1 && part.chars().all(char::is_alphabetic));
+    if ABBREVIATIONS.contains(&lower.as_str()) || initialism {
+        return true;
+    }
+    if NUMBER_ABBREVIATIONS.contains(&lower.as_str()) {
+        return word_after(chars, dot).0.starts_with(|c: char| c.is_ascii_digit());
+    }
+    if NAME_ABBREVIATIONS.contains(&lower.as_str()) {
+        return word.starts_with(char::is_uppercase) && name_follows(chars, dot);
+    }
+    //"et al."
+    if lower == "al" {
+        let (_, before) = word_before(chars, word_start.saturating_sub(1));
+        return word_start > 0 && chars[word_start - 1] == ' ' && before.eq_ignore_ascii_case("et");
+    }
+    //single capital letter: an initial like "A. Turing", not "the C. It is..."
+    let mut letters = word.chars();
+    if let (Some(c), None) = (letters.next(), letters.next()) && c.is_uppercase() {
+        return name_follows(chars, dot);
+    }
+    //"1." / "a." at the start of a line is a list marker
+    let at_line_start: bool = chars[..word_start].iter().rev().take_while(|c| **c != '\n').all(|c| c.is_whitespace());
+    at_line_start && word.chars().count() <= 2 && (word.chars().all(|c| c.is_ascii_digit()) || word.chars().all(|c| c.is_lowercase()))
+}
+
+//A line break ends a sentence unless the next line just continues it (starts lowercase)
+fn line_break_ends_sentence(chars: &[char], newline: usize) -> bool {
+    let next: Option<&char> = chars[newline + 1..].iter().find(|c| **c != ' ' && **c != '\t' && **c != '\r');
+    match next {
+        None | Some('\n') => true,
+        Some(c) => !c.is_lowercase(),
+    }
+}
+
+//Trim whitespace and leading bullet markers (a marker needs whitespace after it: "-5 degrees" keeps its sign);
+//None when nothing is left
+fn make_sentence(chars: &[char], start: usize, end: usize) -> Option<Sentence> {
+    let mut s: usize = start;
+    let mut e: usize = end;
+    while s < e && (chars[s].is_whitespace() || (BULLETS.contains(&chars[s]) && (s + 1 == e || chars[s + 1].is_whitespace()))) {
+        s += 1;
+    }
+    while e > s && chars[e - 1].is_whitespace() {
+        e -= 1;
+    }
+    if s == e {
+        return None;
+    }
+    let text: String = chars[s..e].iter().collect::<String>().split_whitespace().collect::<Vec<&str>>().join(" ");
+    Some(Sentence { text, start: s, end: e })
+}
+
+//Split text into sentences with their char offsets
+pub fn segment(text: &str) -> Vec<Sentence> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut sentences: Vec<Sentence> = Vec::new();
+    let mut start: usize = 0;
+    let mut i: usize = 0;
+
+    while i < chars.len() {
+        let c: char = chars[i];
+        let boundary: Option<usize> = if TERMINATORS.contains(&c) {
+            //keep runs like "?!" or "..." and closing quotes together
+            let mut j: usize = i + 1;
+            while j < chars.len() && (TERMINATORS.contains(&chars[j]) || CLOSERS.contains(&chars[j])) {
+                j += 1;
+            }
+            let followed_by_break: bool = j == chars.len() || chars[j].is_whitespace();
+            if followed_by_break && !(c == '.' && j == i + 1 && is_abbreviation(&chars, i)) {
+                Some(j)
+            } else {
+                None
+            }
+        } else if c == '\n' && line_break_ends_sentence(&chars, i) {
+            Some(i)
+        } else {
+            None
+        };
+
+        match boundary {
+            Some(end) => {
+                sentences.extend(make_sentence(&chars, start, end));
+                start = end;
+                i = end.max(i + 1);
+            }
+            None => i += 1,
+        }
+    }
+    sentences.extend(make_sentence(&chars, start, chars.len()));
+    sentences
+}
+
+//Sentence texts only
+pub fn split_sentences(text: &str) -> Vec<String> {
+    segment(text).into_iter().map(|s| s.text).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_decimals_and_urls_inside_sentences() {
+        assert_eq!(
+            split_sentences("Pi is about 3.14 in most examples. See www.example.com/docs.html for more."),
+            vec!["Pi is about 3.14 in most examples.", "See www.example.com/docs.html for more."]
+        );
+    }
+
+    #[test]
+    fn splits_after_numbers_urls_and_versions() {
+        assert_eq!(split_sentences("Pi is about 3.14. Next we look at joins."), vec!["Pi is about 3.14.", "Next we look at joins."]);
+        assert_eq!(split_sentences("Visit example.com. Then log in."), vec!["Visit example.com.", "Then log in."]);
+        assert_eq!(split_sentences("Use version 1.2.3. Then upgrade."), vec!["Use version 1.2.3.", "Then upgrade."]);
+        assert_eq!(split_sentences("It runs in the U.S. and abroad."), vec!["It runs in the U.S. and abroad."]);
+    }
+
+    #[test]
+    fn bullet_markers_need_a_space_after_them() {
+        assert_eq!(
+            split_sentences("-5 degrees is cold\n- Storage\n*args collects the rest"),
+            vec!["-5 degrees is cold", "Storage", "*args collects the rest"]
+        );
+    }
+
+    #[test]
+    fn splits_bullets_without_a_period() {
+        assert_eq!(
+            split_sentences("Key points\n• Data are raw facts\n• Information is processed data\n- Storage"),
+            vec!["Key points", "Data are raw facts", "Information is processed data", "Storage"]
+        );
+    }
+
+    #[test]
+    fn ends_sentences_at_question_and_exclamation_marks() {
+        assert_eq!(
+            split_sentences("What is a DBMS? It stores data! Really?! \"Yes.\" Done"),
+            vec!["What is a DBMS?", "It stores data!", "Really?!", "\"Yes.\"", "Done"]
+        );
+    }
+
+    #[test]
+    fn joins_wrapped_lines() {
+        assert_eq!(
+            split_sentences("A database is an organized\ncollection of data.\nIt is managed by a DBMS."),
+            vec!["A database is an organized collection of data.", "It is managed by a DBMS."]
+        );
+    }
+
+    #[test]
+    fn numbered_list_items_are_not_sentence_ends() {
+        assert_eq!(
+            split_sentences("1. Collect the data\n2. Clean it\n10. Load it. Then query it."),
+            vec!["1. Collect the data", "2. Clean it", "10. Load it.", "Then query it."]
+        );
+    }
+
+    #[test]
+    fn abbreviations_do_not_end_sentences() {
+        assert_eq!(
+            split_sentences("Use a key, e.g. the student id. Dr. Codd proposed it, cf. Fig. 3 on p. 12 in vol. 2 by Smith et al. in 1970."),
+            vec!["Use a key, e.g. the student id.", "Dr. Codd proposed it, cf. Fig. 3 on p. 12 in vol. 2 by Smith et al. in 1970."]
+        );
+        assert_eq!(split_sentences("The model was proposed by E. F. Codd in 1970."), vec!["The model was proposed by E. F. Codd in 1970."]);
+    }
+
+    #[test]
+    fn short_words_that_look_like_abbreviations_still_end_sentences() {
+        assert_eq!(split_sentences("The answer is no. Next we look at joins."), vec!["The answer is no.", "Next we look at joins."]);
+        assert_eq!(split_sentences("We use the C. It is great."), vec!["We use the C.", "It is great."]);
+        assert_eq!(split_sentences("Carrots have vitamin A. Next comes iron."), vec!["Carrots have vitamin A.", "Next comes iron."]);
+        assert_eq!(split_sentences("Read page 4 of the doc p. Then stop."), vec!["Read page 4 of the doc p.", "Then stop."]);
+    }
+
+    #[test]
+    fn offsets_point_into_the_text() {
+        let text: &str = "First one. Second one.";
+        let chars: Vec<char> = text.chars().collect();
+        for sentence in segment(text) {
+            assert_eq!(chars[sentence.start..sentence.end].iter().collect::<String>(), sentence.text);
+        }
+    }
+}
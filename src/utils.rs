@@ -1,21 +1,28 @@
 use anyhow::Result;
 use reqwest;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
 
-//Fetch real reference links for keywords (Wikipedia links as placeholders)
-pub async fn suggest_resources(keywords: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+//A suggested reading: link text (or the keyword for its own article) and the URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    pub title: String,
+    pub url: String,
+}
+
+//Fetch real reference links for keywords (Wikipedia links as placeholders), with the number of
+//lookups that failed (offline, the list is empty or incomplete and should not be cached)
+pub async fn suggest_resources(keywords: &[String]) -> Result<(Vec<Resource>, usize), Box<dyn std::error::Error + Send + Sync>> {
 
     // Placeholder: In a real implementation, this might query an API or database
     let mut resources = Vec::new();
+    let mut failed: usize = 0;
 
     for kw in keywords {
-        println!("Keyword before filtering: {}", kw);
-
         //Validate keyword:Skip if too long or contains obvious bad char.
         if kw.len() > 50 || kw.contains('/') || kw.contains('?') || kw.trim().is_empty() {
             continue;
         }
-        println!("Keyword before filtering: {}", kw);
 
         let url = format!("https://en.wikipedia.org/wiki/{}", kw.replace(" ", "_"));
 
@@ -25,11 +32,13 @@ pub async fn suggest_resources(keywords: &[String]) -> Result<Vec<String>, Box<d
                 Ok(text) => Ok(text),
                 Err(e) => {
                     eprintln!("Failed to read response text from {}: {}", url, e);
+                    failed += 1;
                     continue;
                 }
             },
             Err(e) => {
                 eprintln!("Failed to fetch {}: {}", url, e);
+                failed += 1;
                 continue;
             }
         };
@@ -44,15 +53,15 @@ pub async fn suggest_resources(keywords: &[String]) -> Result<Vec<String>, Box<d
             if let Some(link) = element.value().attr("href") {
                 if link.starts_with("/wiki/") && !link.contains(":") {
                     let full_link = format!("https://en.wikipedia.org{}", link);
-                    println!("Adding resource URL: {}", full_link); 
-                    resources.push(full_link);
+                    let title: String = element.text().collect::<String>().trim().to_string();
+                    resources.push(Resource { title, url: full_link });
                 }
             }
         }
-        resources.push(url); //add the main wiki page as well
+        resources.push(Resource { title: kw.clone(), url }); //add the main wiki page as well
         
     }
-    Ok(resources)
+    Ok((resources, failed))
     
 }
 
@@ -0,0 +1,112 @@
+//Watch mode: re-analyze the lectures in a folder whenever they change and regenerate their JSON report
+//and summary PDF. Events for a file restart its quiet period (editors save in bursts), and a file whose
+//content hash is the one last analyzed is skipped (touch, metadata changes, saving without edits)
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use crate::export;
+use crate::input;
+use crate::report::{self, AnalysisOptions, AnalysisReport, Analyzers};
+
+//Report files for a lecture, both in out_dir: "week1.pdf.json" and "week1.pdf.summary.pdf"
+fn report_names(path: &Path) -> (String, String) {
+    let name: String = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "lecture".to_string());
+    (format!("{}.json", name), format!("{}.summary.pdf", name))
+}
+
+//Analyze one lecture unless its content is what was analyzed last time; the hash is remembered only after
+//a successful run, so a half-written file is retried on the next save
+async fn update(path: &Path, out_dir: &Path, options: &AnalysisOptions, analyzers: &Analyzers, hashes: &mut HashMap<PathBuf, String>) {
+    let bytes: Vec<u8> = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        //deleted or renamed away before its quiet period ended
+        Err(_) => return,
+    };
+    let hash: String = input::content_hash(&bytes);
+    if hashes.get(path) == Some(&hash) {
+        println!("Unchanged {}", path.display());
+        return;
+    }
+
+    let file: String = path.to_string_lossy().to_string();
+    let result: Result<(String, String), Box<dyn Error + Send + Sync>> = async {
+        let report: AnalysisReport = report::analyze_lecture(&file, &file, options, analyzers).await?;
+        let (json_name, pdf_name) = report_names(path);
+        std::fs::create_dir_all(out_dir)?;
+        std::fs::write(out_dir.join(&json_name), serde_json::to_string_pretty(&report)?)?;
+        export::export_summary_to_pdf(out_dir, &pdf_name, &report)?;
+        Ok((json_name, pdf_name))
+    }
+    .await;
+    match result {
+        Ok((json_name, pdf_name)) => {
+            hashes.insert(path.to_path_buf(), hash);
+            println!("Updated {} -> {}, {}", path.display(), out_dir.join(json_name).display(), out_dir.join(pdf_name).display());
+        }
+        Err(e) => eprintln!("Failed {}: {}", path.display(), e),
+    }
+}
+
+//Analyze every lecture in `dir` once, then each lecture that is created or modified, `debounce` after its last change.
+//Runs until the watcher stops (Ctrl+C ends the process)
+pub async fn watch(dir: &Path, out_dir: &Path, debounce: Duration, options: &AnalysisOptions, analyzers: &Analyzers) -> Result<(), Box<dyn Error + Send + Sync>> {
+    //absolute, so startup paths and event paths are the same keys
+    let dir: PathBuf = dir.canonicalize().map_err(|e| format!("cannot watch {}: {}", dir.display(), e))?;
+    let (sender, mut events) = mpsc::unbounded_channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = sender.send(event);
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    println!("Watching {} (Ctrl+C to stop)", dir.display());
+
+    let mut hashes: HashMap<PathBuf, String> = HashMap::new();
+    //lecture -> time of its last change; the lectures already there are due right away
+    let already_due: Instant = Instant::now().checked_sub(debounce).unwrap_or_else(Instant::now);
+    let mut pending: HashMap<PathBuf, Instant> = input::lectures_in(&dir)?
+        .into_iter()
+        .map(|path| (path, already_due))
+        .collect();
+
+    loop {
+        let next_due: Option<Instant> = pending.values().min().map(|changed| *changed + debounce);
+        tokio::select! {
+            event = events.recv() => match event {
+                Some(Ok(event)) => match event.kind {
+                    EventKind::Create(_) | EventKind::Modify(_) => {
+                        for path in event.paths.into_iter().filter(|p| input::is_supported(p)) {
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                    EventKind::Remove(_) => {
+                        for path in &event.paths {
+                            pending.remove(path);
+                            hashes.remove(path);
+                        }
+                    }
+                    _ => {}
+                },
+                Some(Err(e)) => eprintln!("Watch error: {}", e),
+                None => return Ok(()),
+            },
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {
+                let now: Instant = Instant::now();
+                let mut due: Vec<PathBuf> = pending
+                    .iter()
+                    .filter(|(_, changed)| now.duration_since(**changed) >= debounce)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                due.sort();
+                for path in due {
+                    pending.remove(&path);
+                    if path.is_file() {
+                        update(&path, out_dir, options, analyzers, &mut hashes).await;
+                    }
+                }
+            }
+        }
+    }
+}
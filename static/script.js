@@ -1,9 +1,16 @@
 let selectedFile = null;
+//lecture formats the server reads (checked by extension, browsers report no MIME type for .md)
+const SUPPORTED_EXTENSIONS = ['pdf', 'pptx', 'md', 'markdown', 'html', 'htm', 'txt'];
+
+function isSupported(file) {
+    const extension = file.name.split('.').pop().toLowerCase();
+    return file.type === "application/pdf" || SUPPORTED_EXTENSIONS.includes(extension);
+}
 
 function handleDrop(e) {
     e.preventDefault();
-    //to check if the first file is a PDF
-    if (e.dataTransfer.files.length && e.dataTransfer.files[0].type === "application/pdf") {
+    //to check if the first file is a lecture we can read
+    if (e.dataTransfer.files.length && isSupported(e.dataTransfer.files[0])) {
         selectedFile = e.dataTransfer.files[0];
         //display the selected file name with ID file-name
         document.getElementById('file-name').textContent = selectedFile.name;
@@ -13,7 +20,7 @@ function handleDrop(e) {
 
 function handleFile(e) {
     //target means when the user selects the file using the file input element
-    if (e.target.files.length && e.target.files[0].type === "application/pdf") {
+    if (e.target.files.length && isSupported(e.target.files[0])) {
         selectedFile = e.target.files[0];
         document.getElementById('file-name').textContent = selectedFile.name;
         document.getElementById('summarize-btn').disabled = false;
@@ -29,11 +36,57 @@ async function uploadFile() {
     const formData = new FormData();
     //append(name,value,filename(optional))
     formData.append('file',selectedFile); 
+    formData.append('summarizer', document.getElementById('summarizer').value);
+    formData.append('lang', document.getElementById('lang').value);
+    //empty pages/section analyze the whole lecture
+    formData.append('pages', document.getElementById('pages').value);
+    formData.append('section', document.getElementById('section').value);
 
     const res = await fetch('/api/summarize', {method: 'POST', body: formData});
     const data = await res.json();
-    //If summary is ana array , join it 
-    let summaryText = Array.isArray(data.summary) ? data.summary.join('\n') : data.summary;
+    //failed requests carry only {error}
+    if (!res.ok || data.error) {
+        document.getElementById('summary').textContent = data.error || `Request failed (${res.status})`;
+        document.getElementById('summarize-btn').disabled = false;
+        return;
+    }
+    //The server returns the analysis report: summary is an array of {text, page, start, end}, show the page each sentence came from
+    let summaryText = data.summary.map(s => s.page ? `${s.text} (page ${s.page})` : s.text).join('\n');
+    //Title from the lecture metadata on top, per-section summaries (lectures with bookmarks/headings) below
+    const metadata = data.source.metadata;
+    if (metadata.title) {
+        summaryText = `${metadata.title} (${metadata.page_count} pages)\n\n${summaryText}`;
+    }
+    if (Array.isArray(data.sections) && data.sections.length) {
+        summaryText += '\n\nSections:\n' + data.sections
+            .map(s => `${s.title}\n` + s.summary.map(x => `  - ${x.text}`).join('\n'))
+            .join('\n');
+    }
+    //Code listings, tables and formulas are kept out of the summary but listed after it
+    if (Array.isArray(data.blocks) && data.blocks.length) {
+        const labels = {code: 'Code', table: 'Table', math: 'Formula'};
+        summaryText += '\n\nCode, Tables and Formulas:\n' + data.blocks
+            .map(b => `${labels[b.kind] || b.kind} (page ${b.page}):\n` + (b.kind === 'table'
+                ? b.rows.map(row => '    ' + row.join(' | '))
+                : b.text.split('\n').map(line => '    ' + line)).join('\n'))
+            .join('\n');
+    }
+    //Keywords with the pages they appear on, suggested readings as "title - url"
+    if (data.keywords.length) {
+        summaryText += '\n\nKeywords:\n' + data.keywords.map(k => `- ${k.keyword} (pages ${k.pages.join(', ')})`).join('\n');
+    }
+    //Glossary: the lecture's own definition of each keyword, keywords it never defines listed after
+    if (Array.isArray(data.glossary) && data.glossary.length) {
+        const defined = data.glossary.filter(g => g.definition);
+        const undefinedTerms = data.glossary.filter(g => !g.definition).map(g => g.term);
+        summaryText += '\n\nGlossary:\n' + defined.map(g => `- ${g.term} (page ${g.page}): ${g.definition}`).join('\n');
+        if (undefinedTerms.length) {
+            summaryText += `\nNot defined in the lecture: ${undefinedTerms.join(', ')}`;
+        }
+    }
+    if (data.resources.length) {
+        summaryText += '\n\nResources:\n' + data.resources.map(r => `- ${r.title} - ${r.url}`).join('\n');
+    }
     //display the summary result(received from backend)
     document.getElementById('summary').textContent = summaryText;
 
@@ -43,7 +96,7 @@ async function uploadFile() {
     //set up download button
     //Blob(["Blob content"], {type:MIME}) give Js temp. files and URL.createObjectURL create a URL for the blob (a collection of binary data stored as a file)
     dl.onclick = () => {
-        const blob = new Blob([data.summary], {type: 'text/plain'}); 
+        const blob = new Blob([summaryText], {type: 'text/plain'}); 
         const url=  URL.createObjectURL(blob);
         //create a hidden <a> element,set its href to the file,trigger click to download 
         const a  =document.createElement('a');
@@ -0,0 +1,65 @@
+//Header/footer and page number removal on small made-up lectures (cargo test --test clean)
+#![allow(dead_code)]
+
+#[path = "../src/pdf.rs"]
+mod pdf;
+#[path = "../src/layout.rs"]
+mod layout;
+#[path = "../src/clean.rs"]
+mod clean;
+
+use clean::{remove_boilerplate, CleanReport, RemovalReason};
+
+fn document(pages: &[String]) -> pdf::Document {
+    let pages: Vec<pdf::Page> = pages
+        .iter()
+        .enumerate()
+        .map(|(i, text)| pdf::Page { number: i as u32 + 1, text: text.clone() })
+        .collect();
+    pdf::Document { pages, ..Default::default() }
+}
+
+//Four slides with the course code on top, a body and "Page n of 4" at the bottom
+fn slides(bodies: &[&str]) -> Vec<String> {
+    bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| format!("CS2040 Database Systems\n{}\nPage {} of {}", body, i + 1, bodies.len()))
+        .collect()
+}
+
+#[test]
+fn removes_repeated_headers_and_page_numbers() {
+    let mut lecture: pdf::Document = document(&slides(&[
+        "Tables hold rows.\nEach row has columns.\nKeys identify rows.",
+        "Indexes speed up lookups.\nThey cost space.\nB-trees are common.",
+        "Joins combine tables.\nThey match keys.\nHash joins are fast.",
+        "Transactions are atomic.\nThey commit or abort.\nLogs make them durable.",
+    ]));
+    let report: CleanReport = remove_boilerplate(&mut lecture);
+    assert_eq!(lecture.pages[0].text, "Tables hold rows.\nEach row has columns.\nKeys identify rows.");
+    let reasons: Vec<(&str, RemovalReason, usize)> = report.removed.iter().map(|r| (r.text.as_str(), r.reason, r.pages.len())).collect();
+    assert!(reasons.contains(&("CS2040 Database Systems", RemovalReason::Repeated, 4)));
+    assert!(reasons.contains(&("Page 1 of 4", RemovalReason::PageNumber, 4)));
+}
+
+#[test]
+fn keeps_headings_repeated_in_the_body() {
+    let mut lecture: pdf::Document = document(&slides(&[
+        "Tables hold rows.\nExample\nA student table.\nKeys identify rows.",
+        "Indexes speed up lookups.\nExample\nAn index on names.\nB-trees are common.",
+        "Joins combine tables.\nExample\nStudents with courses.\nHash joins are fast.",
+        "Transactions are atomic.\nExample\nA bank transfer.\nLogs make them durable.",
+    ]));
+    let report: CleanReport = remove_boilerplate(&mut lecture);
+    assert!(lecture.pages.iter().all(|page| page.text.contains("\nExample\n")));
+    assert!(report.removed.iter().all(|r| r.text != "Example"));
+}
+
+#[test]
+fn short_lectures_keep_everything_but_page_numbers() {
+    let mut lecture: pdf::Document = document(&slides(&["Tables hold rows.\nKeys identify rows.", "Joins combine tables.\nThey match keys."]));
+    remove_boilerplate(&mut lecture);
+    assert!(lecture.pages[0].text.starts_with("CS2040 Database Systems"));
+    assert!(!lecture.pages[1].text.contains("Page 2 of 2"));
+}
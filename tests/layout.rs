@@ -0,0 +1,186 @@
+//Layout reading order checks on a synthetic two-column handout (cargo test --test layout)
+#![allow(dead_code)]
+
+#[path = "../src/pdf.rs"]
+mod pdf;
+#[path = "../src/layout.rs"]
+mod layout;
+#[path = "../src/clean.rs"]
+mod clean;
+
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Object, Stream};
+
+const TITLE: &str = "Reading Order in Two Column Handouts";
+const LEFT: &[&str] = &[
+    "The left column opens the handout",
+    "and keeps going for several lines",
+    "until the first paragraph ends.",
+    "A second left paragraph follows",
+    "right below the first one.",
+];
+const RIGHT: &[&str] = &[
+    "The right column is read after",
+    "the whole left column is done",
+    "so its sentences stay intact.",
+    "Its last paragraph closes",
+    "the page as expected.",
+];
+const FOOTER: &str = "Department of Computing, Database Systems handout, page one";
+
+fn show(x: i64, y: i64, size: i64, text: &str) -> Vec<Operation> {
+    vec![
+        Operation::new("BT", vec![]),
+        Operation::new("Tf", vec!["F1".into(), size.into()]),
+        Operation::new("Td", vec![x.into(), y.into()]),
+        Operation::new("Tj", vec![Object::string_literal(text)]),
+        Operation::new("ET", vec![]),
+    ]
+}
+
+//One US letter page with a title, two columns written row by row (left line, then the right line
+//on the same baseline, the way many generators interleave them) and a full-width footer
+fn two_column_pdf() -> tempfile::NamedTempFile {
+    let mut operations: Vec<Operation> = show(120, 740, 18, TITLE);
+    for (row, (left, right)) in LEFT.iter().zip(RIGHT).enumerate() {
+        let y: i64 = 700 - row as i64 * 14 - if row >= 3 { 14 } else { 0 };
+        operations.extend(show(72, y, 10, left));
+        operations.extend(show(320, y, 10, right));
+    }
+    operations.extend(show(72, 60, 10, FOOTER));
+
+    let mut doc: lopdf::Document = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let resources_id = doc.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+    let content: Content = Content { operations };
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => resources_id,
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+    });
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![page_id.into()],
+        "Count" => 1,
+    }));
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+
+    let file: tempfile::NamedTempFile = tempfile::Builder::new().suffix(".pdf").tempfile().unwrap();
+    doc.save(file.path()).unwrap();
+    file
+}
+
+fn page_text(layout: bool) -> String {
+    let file: tempfile::NamedTempFile = two_column_pdf();
+    let document: pdf::Document = pdf::extract_document(&file.path().to_string_lossy(), None, layout, &pdf::PageArgs::default()).unwrap();
+    document.text()
+}
+
+fn position(text: &str, line: &str) -> usize {
+    text.find(line).unwrap_or_else(|| panic!("{:?} missing from {:?}", line, text))
+}
+
+#[test]
+fn stream_order_interleaves_the_columns() {
+    let text: String = page_text(false);
+    assert!(position(&text, RIGHT[0]) < position(&text, LEFT[1]));
+}
+
+#[test]
+fn layout_reads_each_column_top_to_bottom() {
+    let text: String = page_text(true);
+    let order: Vec<&str> = std::iter::once(TITLE).chain(LEFT.iter().cloned()).chain(RIGHT.iter().cloned()).chain([FOOTER]).collect();
+    for pair in order.windows(2) {
+        assert!(position(&text, pair[0]) < position(&text, pair[1]), "{:?} should come before {:?} in {:?}", pair[0], pair[1], text);
+    }
+}
+
+#[test]
+fn layout_keeps_lines_and_paragraphs() {
+    let text: String = page_text(true);
+    assert!(text.contains(&format!("{}\n{}", LEFT[0], LEFT[1])));
+    assert!(text.contains(&format!("{}\n\n{}", LEFT[2], LEFT[3])));
+    assert!(text.contains(&format!("{}\n{}", LEFT[4], RIGHT[0])));
+}
+
+//One line per page in Helvetica ("F1"); a page naming another font resource has no font pdf-extract can use
+fn pages_pdf(pages: &[(&str, &str)]) -> tempfile::NamedTempFile {
+    let mut doc: lopdf::Document = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let resources_id = doc.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+    let mut kids: Vec<Object> = Vec::new();
+    for (font, text) in pages {
+        let operations: Vec<Operation> = vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec![(*font).into(), 12.into()]),
+            Operation::new("Td", vec![72.into(), 700.into()]),
+            Operation::new("Tj", vec![Object::string_literal(*text)]),
+            Operation::new("ET", vec![]),
+        ];
+        let content_id = doc.add_object(Stream::new(dictionary! {}, Content { operations }.encode().unwrap()));
+        kids.push(doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }).into());
+    }
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Count" => kids.len() as i64,
+        "Kids" => kids,
+    }));
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+
+    let file: tempfile::NamedTempFile = tempfile::Builder::new().suffix(".pdf").tempfile().unwrap();
+    doc.save(file.path()).unwrap();
+    file
+}
+
+fn extract_pages(file: &tempfile::NamedTempFile, selection: &pdf::PageArgs) -> Vec<(u32, String)> {
+    let document: pdf::Document = pdf::extract_document(&file.path().to_string_lossy(), None, false, selection).unwrap();
+    document.pages.into_iter().map(|p| (p.number, p.text)).collect()
+}
+
+#[test]
+fn splits_the_text_into_numbered_pages() {
+    let file: tempfile::NamedTempFile = pages_pdf(&[("F1", "First page"), ("F1", "Second page"), ("F1", "Third page")]);
+    let pages: Vec<(u32, String)> = extract_pages(&file, &pdf::PageArgs::default());
+    assert_eq!(pages, vec![(1, "First page".to_string()), (2, "Second page".to_string()), (3, "Third page".to_string())]);
+}
+
+#[test]
+fn extracts_only_the_selected_pages() {
+    let file: tempfile::NamedTempFile = pages_pdf(&[("F1", "First page"), ("F1", "Second page"), ("F1", "Third page")]);
+    let selection: pdf::PageArgs = pdf::PageArgs { pages: Some("1,3".parse().unwrap()), ..Default::default() };
+    let pages: Vec<(u32, String)> = extract_pages(&file, &selection);
+    assert_eq!(pages, vec![(1, "First page".to_string()), (3, "Third page".to_string())]);
+}
+
+#[test]
+fn pages_after_a_failing_page_are_still_extracted() {
+    let file: tempfile::NamedTempFile = pages_pdf(&[("F1", "First page"), ("F9", "Broken page"), ("F1", "Third page")]);
+    let pages: Vec<(u32, String)> = extract_pages(&file, &pdf::PageArgs::default());
+    assert_eq!(pages.len(), 3);
+    assert_eq!(pages[0], (1, "First page".to_string()));
+    assert_eq!(pages[1].0, 2);
+    assert_eq!(pages[2], (3, "Third page".to_string()));
+}
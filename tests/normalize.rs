@@ -0,0 +1,64 @@
+//Normalization checks, run against the sample lecture in the repo root (cargo test --test normalize)
+#![allow(dead_code)]
+
+#[path = "../src/pdf.rs"]
+mod pdf;
+#[path = "../src/layout.rs"]
+mod layout;
+#[path = "../src/clean.rs"]
+mod clean;
+#[path = "../src/normalize.rs"]
+mod normalize;
+
+use normalize::normalize_text;
+
+fn sample_lecture() -> pdf::Document {
+    let mut document: pdf::Document = pdf::extract_document(concat!(env!("CARGO_MANIFEST_DIR"), "/my_lecture.pdf"), None, false, &pdf::PageArgs::default()).unwrap();
+    normalize::normalize_document(&mut document);
+    document
+}
+
+#[test]
+fn folds_ligatures_and_odd_spaces() {
+    assert_eq!(normalize_text("de\u{fb01}ne the \u{fb02}ow\u{a0}of data\u{200b}"), "define the flow of data");
+    assert_eq!(normalize_text("ＤＢＭＳ…"), "DBMS...");
+}
+
+#[test]
+fn rejoins_hyphenated_words() {
+    assert_eq!(normalize_text("raw facts become informa-\ntion"), "raw facts become information");
+    assert_eq!(normalize_text("soft hyphen in infor\u{ad}\nmation"), "soft hyphen in information");
+    assert_eq!(normalize_text("mid\u{ad}word"), "midword");
+}
+
+#[test]
+fn merges_wrapped_lines_but_keeps_bullets_and_paragraphs() {
+    let text: &str = "Introducing the Database \nand the DBMS\n\n• Data vs Information\n• Database vs DBMS\n\n\n\nNext   slide";
+    assert_eq!(
+        normalize_text(text),
+        "Introducing the Database and the DBMS\n\n• Data vs Information\n• Database vs DBMS\n\nNext slide"
+    );
+}
+
+#[test]
+fn sample_lecture_has_no_broken_lines_or_stray_whitespace() {
+    let document: pdf::Document = sample_lecture();
+    let text: String = document.text();
+    assert!(text.contains("why they are valuable assets for decision making"));
+    assert!(text.contains("Introducing the Database and the DBMS"));
+    for line in text.lines() {
+        assert_eq!(line, line.trim(), "untrimmed line {:?}", line);
+        assert!(!line.contains("  "), "repeated spaces in {:?}", line);
+        assert!(!line.starts_with(char::is_lowercase), "unmerged wrapped line {:?}", line);
+    }
+    assert!(!text.contains("\n\n\n"));
+    assert!(!text.chars().any(|c| ('\u{fb00}'..='\u{fb06}').contains(&c) || c == '\u{ad}'));
+}
+
+#[test]
+fn normalizing_twice_changes_nothing() {
+    let document: pdf::Document = sample_lecture();
+    for page in &document.pages {
+        assert_eq!(normalize_text(&page.text), page.text);
+    }
+}